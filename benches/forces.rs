@@ -0,0 +1,47 @@
+//! Times one force calculation over worlds of 10 000 blobs and more, through
+//! the spatial hash and, for the smallest world, by comparing every pair.
+//!
+//! Run with `cargo bench --bench forces`.
+
+use std::time::{Duration, Instant};
+
+use blobbin::app::World;
+use blobbin::common::config::PhysicsConfig;
+
+const SEED: u64 = 42;
+const BLOB_COUNTS: &[usize] = &[10_000, 20_000, 50_000];
+const RUNS: u32 = 10;
+
+/// The average time `f` takes over `RUNS` runs, after one to warm up.
+fn time(mut f: impl FnMut()) -> Duration {
+    f();
+
+    let start = Instant::now();
+    for _ in 0..RUNS {
+        f();
+    }
+    start.elapsed() / RUNS
+}
+
+/// A world with the same density of blobs whatever the count.
+fn world(blobs: usize) -> World {
+    let side = ((blobs * 25) as f64).sqrt() as usize;
+    let mut world = World::with_seed(side, side, PhysicsConfig::default(), SEED);
+    world.add_random_blobs(blobs);
+    world
+}
+
+fn main() {
+    for &blobs in BLOB_COUNTS {
+        let mut world = world(blobs);
+        let elapsed = time(|| {
+            world.forces();
+        });
+        println!("spatial hash, {:>6} blobs: {:?}", blobs, elapsed);
+    }
+
+    let mut world = world(BLOB_COUNTS[0]);
+    let start = Instant::now();
+    world.brute_force_forces();
+    println!("brute force,  {:>6} blobs: {:?}", BLOB_COUNTS[0], start.elapsed());
+}
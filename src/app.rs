@@ -1,48 +1,272 @@
 pub mod state;
 pub mod blob;
+pub mod boundary;
+pub mod collision;
+pub mod forces;
+pub mod input;
+pub mod integrators;
+pub mod playback;
+pub mod recording;
+pub mod scene;
+pub mod spatial_hash;
+pub mod timestep;
+pub mod tools;
 pub mod world;
 
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::time::{Instant, Duration};
 use winit::event::{Event, VirtualKeyCode};
 use winit::event_loop::{EventLoop, ControlFlow};
+use winit_input_helper::WinitInputHelper;
 
-pub use blob::Blob;
+use crate::common::config::{Config, ConfigLoader, ConfigWatcher};
+use crate::common::events::Subscriber;
+use crate::common::math::Vector2f;
+use crate::graphics::{self, InstanceId};
+
+pub use blob::{Blob, BlobEvent, BlobId};
+pub use collision::Contact;
+pub use forces::{Force, ForceSet};
+pub use input::{InputLog, WorldInput};
+pub use integrators::Integrator;
+pub use playback::Playback;
+pub use recording::{RecordedFrame, Recorder, Recording, RecordingHeader, RECORDING_VERSION};
+pub use scene::{Scene, SceneFormat, SCENE_VERSION};
+pub use spatial_hash::SpatialHash;
 pub use state::AppState;
+pub use timestep::FixedTimestep;
+pub use tools::{MouseButton, Tool, Tools};
 pub use world::World;
 
 const FRAME_TIME: u64 = 1000 / 60;
-const CONFIG_REFRESH_RATE: Duration = Duration::from_secs(5);
+const BLOB_RESOLUTION: u16 = 16;
+const TICK_TIME: f64 = 1.0 / 60.0;
+
+/// How many frames Page Up and Page Down skip during playback.
+const PLAYBACK_SKIP_FRAMES: isize = 60;
 
 pub struct App {
+    config: Config,
     world: World,
+    timestep: FixedTimestep,
+    input_log: InputLog,
     last_event: Instant,
     last_frame: Instant,
-    last_config_refresh: Instant,
+    config_watcher: Option<ConfigWatcher>,
+    recorder: Option<(Recorder, PathBuf)>,
+    playback: Option<Playback>,
+    blob_events: Subscriber<BlobEvent>,
+    blob_instances: HashMap<BlobId, InstanceId>,
+    tools: Tools,
 }
 
 impl App {
-    pub fn new(world: World) -> Self {
+    /// `config` sets up the window and renderer; the world keeps its own
+    /// physics config.
+    pub fn new(config: Config, mut world: World) -> Self {
+        let blob_events = world.subscribe_to_blobs();
+
         Self {
+            config,
             world,
+            timestep: FixedTimestep::new(TICK_TIME),
+            input_log: InputLog::new(),
             last_event: Instant::now(),
             last_frame: Instant::now(),
-            last_config_refresh: Instant::now(),
+            config_watcher: None,
+            recorder: None,
+            playback: None,
+            blob_events,
+            blob_instances: HashMap::new(),
+            tools: Tools::new(),
+        }
+    }
+
+    /// Play back a recording instead of running a simulation.
+    pub fn replay(config: Config, recording: Recording) -> Self {
+        let world = recording.header.world();
+
+        let mut app = Self::new(config, world);
+        app.playback = Some(Playback::new(recording));
+        app
+    }
+
+    /// Record every tick from now on, saving to `path` when the app exits.
+    pub fn record_to(&mut self, path: impl Into<PathBuf>) {
+        let recorder = Recorder::new(&self.world, TICK_TIME);
+        self.recorder = Some((recorder, path.into()));
+    }
+
+    /// Re-resolve the config whenever one of `loader`'s files changes on disk,
+    /// applying it to the window, renderer and world.
+    ///
+    /// Physics changes reach the world as [`WorldInput::SetConfig`], so they
+    /// are in the input log and replay like any other input.
+    pub fn watch_config(&mut self, loader: ConfigLoader) -> anyhow::Result<()> {
+        let mut current = self.config.clone();
+        current.physics = self.world.config.clone();
+
+        self.config_watcher = Some(ConfigWatcher::new(loader, current)?);
+
+        Ok(())
+    }
+
+    /// Every input applied to the world so far; together with the world's seed
+    /// and config this is enough to replay the session.
+    pub fn input_log(&self) -> &InputLog {
+        &self.input_log
+    }
+
+    /// Add blobs at random places, as an input so they end up in recordings.
+    pub fn add_random_blobs(&mut self, count: usize) {
+        self.apply_input(WorldInput::AddRandomBlobs { count });
+    }
+
+    fn apply_input(&mut self, input: WorldInput) {
+        self.world.apply_input(&input);
+        self.input_log.push(self.world.tick(), input);
+    }
+
+    fn update_world(&mut self) {
+        for input in self.tools.tick(&self.world, self.timestep.step()) {
+            self.apply_input(input);
+        }
+
+        let tick = self.world.tick();
+        self.world.update(self.timestep.step());
+
+        if let Some((recorder, _)) = &mut self.recorder {
+            let inputs: Vec<WorldInput> = self.input_log.inputs_at(tick).cloned().collect();
+            recorder.record(&self.world, &inputs);
+        }
+    }
+
+    /// Add and remove blob instances as blobs spawn and despawn, then move
+    /// every instance to where its blob is drawn `alpha` of the way through
+    /// the tick.
+    fn sync_blob_instances(&mut self, graphics: &mut graphics::State, object: usize, alpha: f64) {
+        for event in self.blob_events.drain() {
+            match event {
+                BlobEvent::Spawned(id) => {
+                    // Blobs already in the world when `run` started have an instance
+                    if self.blob_instances.contains_key(&id) {
+                        continue;
+                    }
+                    if let Some(blob) = self.world.blob(id) {
+                        let instance = blob.to_instance(alpha, &self.world.config);
+                        if let Some(instance) = graphics.add_instance(object, instance) {
+                            self.blob_instances.insert(id, instance);
+                        }
+                    }
+                }
+                BlobEvent::Despawned(id) => {
+                    if let Some(instance) = self.blob_instances.remove(&id) {
+                        graphics.remove_instance(object, instance);
+                    }
+                }
+            }
+        }
+
+        for blob in &self.world.blobs {
+            if let Some(&instance) = self.blob_instances.get(&blob.id) {
+                graphics.update_instance(object, instance, blob.to_instance(alpha, &self.world.config));
+            }
+        }
+    }
+
+    /// 1, 2 and 3 pick the grab, paint and push tools, Tab cycles through the
+    /// species new blobs are added as and the mouse uses the current tool.
+    fn control_tools(&mut self, state: &AppState) {
+        let input = &state.input;
+        let mut inputs = Vec::new();
+
+        for &(key, tool) in &[
+            (VirtualKeyCode::Key1, Tool::Grab),
+            (VirtualKeyCode::Key2, Tool::Paint),
+            (VirtualKeyCode::Key3, Tool::Push),
+        ] {
+            if input.key_pressed(key) {
+                inputs.extend(self.tools.select(tool));
+            }
+        }
+        if input.key_pressed(VirtualKeyCode::Tab) {
+            let count = self.world.config.species.len().max(1);
+            self.tools.species = (self.tools.species + 1) % count;
+        }
+
+        let cursor = input
+            .mouse()
+            .and_then(|(x, y)| state.graphics.screen_to_world(x, y))
+            .map(|point| Vector2f::new(point.x as f64, point.y as f64));
+        self.tools.move_cursor(cursor);
+
+        for &(index, button) in &[(0, MouseButton::Primary), (1, MouseButton::Secondary)] {
+            if input.mouse_pressed(index) {
+                inputs.extend(self.tools.press(button, &self.world));
+            }
+            if input.mouse_released(index) {
+                inputs.extend(self.tools.release(button));
+            }
+        }
+
+        for world_input in inputs {
+            self.apply_input(world_input);
+        }
+    }
+
+    fn save_recording(&mut self) {
+        if let Some((recorder, path)) = self.recorder.take() {
+            match recorder.save(&path) {
+                Ok(()) => log::info!("Saved {} frames to {}", recorder.frame_count(), path.display()),
+                Err(error) => log::error!("Failed to save recording: {:#}", error),
+            }
         }
     }
 
     pub fn run(mut self) -> ! {
         let event_loop = EventLoop::new();
-        let mut state = AppState::new(self.world.width as u32, self.world.height as u32, &event_loop).unwrap();
+        let mut state = AppState::new(&self.config, &event_loop).unwrap();
+        let config_events = self.config_watcher.as_mut().map(ConfigWatcher::subscribe);
         self.last_frame = Instant::now();
 
+        let blob_shape = graphics::shape::circle(graphics::color::WHITE, BLOB_RESOLUTION);
+        let blob_object = state.graphics.create_object(&blob_shape.vertices, &blob_shape.indices);
+        for blob in &self.world.blobs {
+            let instance = blob.to_instance(1.0, &self.world.config);
+            if let Some(instance) = state.graphics.add_instance(blob_object, instance) {
+                self.blob_instances.insert(blob.id, instance);
+            }
+        }
+
+        frame_world(&mut state.graphics, &self.world);
+
         event_loop.run(move |event, _, control_flow| {
             let current_event = Instant::now();
             let delta_time = (current_event - self.last_event).as_secs_f64();
             self.last_event = current_event;
 
-            if current_event - self.last_config_refresh >= CONFIG_REFRESH_RATE {
-                let _ = self.world.refresh_config();
-                self.last_config_refresh = current_event;
+            if let Some(reloaded) = self.config_watcher.as_mut().and_then(ConfigWatcher::poll) {
+                state.show_config_error(reloaded.err().as_ref());
+            }
+            if let Some(event) = config_events.as_ref().and_then(|events| events.latest()) {
+                state.apply_config(&event.current);
+                if self.playback.is_none() {
+                    if event.current.physics != self.world.config {
+                        let config = event.current.physics.clone();
+                        self.apply_input(WorldInput::SetConfig { config });
+                    }
+
+                    let window = &event.current.window;
+                    if window.width != event.previous.window.width || window.height != event.previous.window.height {
+                        self.apply_input(WorldInput::Resize {
+                            width: window.width as usize,
+                            height: window.height as usize,
+                        });
+                        frame_world(&mut state.graphics, &self.world);
+                    }
+                }
+                self.config = event.current;
             }
 
             // Draw the current frame
@@ -50,44 +274,99 @@ impl App {
                 let current_frame = Instant::now();
                 if current_frame - self.last_frame >= Duration::from_millis(FRAME_TIME) {
                     self.last_frame = current_frame;
-                    self.world.draw(state.pixels.get_frame());
-                    if state.pixels
-                        .render()
-                        .map_err(|e| log::error!("pixels.render() failed: {}", e))
-                        .is_err()
-                    {
-                        *control_flow = ControlFlow::Exit;
-                        return;
+
+                    match &self.playback {
+                        Some(playback) => {
+                            state.graphics.set_instances(blob_object, playback.instances());
+                        }
+                        None => {
+                            let alpha = self.timestep.alpha();
+                            self.sync_blob_instances(&mut state.graphics, blob_object, alpha);
+                        }
                     }
+
+                    state.graphics.update();
+                    state.graphics.render();
                 }
             }
+
+            // Camera controls
+            if let Event::WindowEvent { event: window_event, .. } = &event {
+                state.graphics.input(window_event);
+            }
     
             // Handle input events
             if state.input.update(&event) {
                 // Close events
                 if state.input.key_pressed(VirtualKeyCode::Escape) || state.input.quit() {
+                    self.save_recording();
                     *control_flow = ControlFlow::Exit;
                     return;
                 }
     
                 // Resize the window
                 if let Some(size) = state.input.window_resized() {
-                    state. pixels.resize(size.width, size.height);
+                    state.graphics.resize(size);
                 }
     
-                if state.input.mouse_released(0) {
-                    if let Some((x, y)) = state.input.mouse() {
-                        let x = (x / 2.0) as f64;
-                        let y = (y / 2.0) as f64;
-                        self.world.add_blob(x, y);
-                    }
+                if let Some(playback) = &mut self.playback {
+                    control_playback(&state.input, playback);
+                } else {
+                    self.control_tools(&state);
                 }
             }
 
-            // Update internal state and request a redraw
-            self.world.update(delta_time);
+            // Update internal state in fixed steps and request a redraw
+            if let Some(playback) = &mut self.playback {
+                playback.update(delta_time);
+            } else {
+                for _ in 0..self.timestep.advance(delta_time) {
+                    self.update_world();
+                }
+            }
             state.window.request_redraw();            
         });
     }
 }
 
+/// Point the camera at the whole of `world`.
+fn frame_world(graphics: &mut graphics::State, world: &World) {
+    let center = cgmath::Point2::new(world.width as f32 / 2.0, world.height as f32 / 2.0);
+    graphics.camera_mut().frame(center, world.height as f32);
+}
+
+/// Space pauses, `,` and `.` step a frame, Page Up and Page Down skip a
+/// second, Home and End jump to either end and `-` and `=` halve or double
+/// the speed.
+fn control_playback(input: &WinitInputHelper, playback: &mut Playback) {
+    if input.key_pressed(VirtualKeyCode::Space) {
+        playback.toggle_pause();
+    }
+
+    if input.key_pressed(VirtualKeyCode::Comma) {
+        playback.step(-1);
+    }
+    if input.key_pressed(VirtualKeyCode::Period) {
+        playback.step(1);
+    }
+    if input.key_pressed(VirtualKeyCode::PageUp) {
+        playback.step(-PLAYBACK_SKIP_FRAMES);
+    }
+    if input.key_pressed(VirtualKeyCode::PageDown) {
+        playback.step(PLAYBACK_SKIP_FRAMES);
+    }
+    if input.key_pressed(VirtualKeyCode::Home) {
+        playback.seek(0);
+    }
+    if input.key_pressed(VirtualKeyCode::End) {
+        playback.seek(playback.frame_count());
+    }
+
+    if input.key_pressed(VirtualKeyCode::Minus) {
+        playback.set_speed(playback.speed() / 2.0);
+    }
+    if input.key_pressed(VirtualKeyCode::Equals) {
+        playback.set_speed(playback.speed() * 2.0);
+    }
+}
+
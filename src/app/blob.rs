@@ -1,28 +1,131 @@
-use crate::common::math::Vector2f;
+use serde::{Deserialize, Serialize};
+use std::fmt;
 
-#[derive(Default, Debug, Clone)]
+use crate::{
+    common::{
+        config::{CollisionShape, PhysicsConfig},
+        math::Vector2f,
+    },
+    graphics::{color, Color, Instance},
+};
+
+/// A stable handle to a blob in a `World`.
+///
+/// Handles are never reused, so one stays tied to its blob even after the
+/// blob is removed and others are added.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct BlobId(u64);
+
+impl BlobId {
+    pub const fn new(id: u64) -> Self {
+        BlobId(id)
+    }
+
+    pub fn get(self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for BlobId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Published by a `World` whenever a blob is added or removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlobEvent {
+    Spawned(BlobId),
+    Despawned(BlobId),
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Blob {
-    pub id: usize,
+    pub id: BlobId,
+    /// Index into `physics.species`. Ignored if there is no such species.
+    #[serde(default)]
+    pub species: usize,
     pub size: f64,
+    /// Missing from version 3 and older scenes, in which case it is filled in
+    /// from `size` when loading. See [`mass_for_size`].
+    #[serde(default)]
+    pub mass: f64,
     pub position: Vector2f,
+    /// Where the blob was before the last `World::update`, for interpolation.
+    pub previous_position: Vector2f,
     pub velocity: Vector2f,
     pub acceleration: Vector2f,
 }
 
 impl Blob {
-    pub fn new(id: usize, size: f64, position: impl Into<Vector2f>) -> Self {
+    pub fn new(id: BlobId, size: f64, position: impl Into<Vector2f>) -> Self {
+        let position = position.into();
         Self {
             id,
             size,
-            position: position.into(),
+            mass: 1.0,
+            previous_position: position.clone(),
+            position,
             ..Default::default()
         }
     }
 
-    pub fn contains_point(&self, point: &Vector2f) -> bool {
-        point.x >= self.position.x 
-            && point.x <= self.position.x + self.size
-            && point.y >= self.position.y
-            && point.y <= self.position.y + self.size
+    pub fn radius(&self) -> f64 {
+        self.size / 2.0
+    }
+
+    /// `position` is the top left corner of the blob's square.
+    pub fn center(&self) -> Vector2f {
+        let radius = self.radius();
+        &self.position + Vector2f::new(radius, radius)
+    }
+
+    /// The color of the blob's species, or white if it has none.
+    pub fn color(&self, config: &PhysicsConfig) -> Color {
+        config.species.get(self.species).map_or(color::WHITE, |species| {
+            let [r, g, b] = species.color;
+            [r as f32, g as f32, b as f32]
+        })
+    }
+
+    /// Linearly interpolate between the previous and current position.
+    pub fn interpolated_position(&self, alpha: f64) -> Vector2f {
+        &self.previous_position + alpha * (&self.position - &self.previous_position)
+    }
+
+    /// An instance of a unit circle covering this blob, drawn `alpha` of the
+    /// way between its previous and current position in its species' color.
+    pub fn to_instance(&self, alpha: f64, config: &PhysicsConfig) -> Instance {
+        use cgmath::Rotation3;
+
+        let radius = self.radius();
+        let center = &self.interpolated_position(alpha) + Vector2f::new(radius, radius);
+
+        Instance {
+            position: cgmath::Vector3::new(center.x as f32, center.y as f32, 0.0),
+            rotation: cgmath::Quaternion::from_axis_angle(cgmath::Vector3::unit_z(), cgmath::Deg(0.0)),
+            scale: radius as f32,
+            color: self.color(config),
+        }
+    }
+
+    /// Whether `point` is inside the blob, treating it as the same `shape` it
+    /// collides as.
+    pub fn contains_point(&self, point: &Vector2f, shape: CollisionShape) -> bool {
+        match shape {
+            CollisionShape::Circle => self.center().vector_to(point).magnitude() <= self.radius(),
+            CollisionShape::Aabb => {
+                point.x >= self.position.x
+                    && point.x <= self.position.x + self.size
+                    && point.y >= self.position.y
+                    && point.y <= self.position.y + self.size
+            }
+        }
     }
 }
+
+/// The mass of a blob of `size`, in proportion to its area, where a blob of
+/// `blob_size` has a mass of 1.
+pub fn mass_for_size(size: f64, blob_size: f64) -> f64 {
+    (size / blob_size).powi(2)
+}
@@ -0,0 +1,100 @@
+use crate::{
+    app::{Blob, BlobId},
+    common::{
+        config::{BoundaryConfig, BoundaryMode},
+        math::Vector2f,
+    },
+};
+
+/// Keep `blobs` within a `width` by `height` world as `config` says, returning
+/// the ids of any that were absorbed.
+pub fn apply(blobs: &mut Vec<Blob>, config: &BoundaryConfig, width: f64, height: f64) -> Vec<BlobId> {
+    let mut absorbed = Vec::new();
+
+    match config.mode {
+        BoundaryMode::Reflect => {
+            for blob in blobs.iter_mut() {
+                reflect(blob, config.restitution, width, height);
+            }
+        }
+        BoundaryMode::Wrap => {
+            for blob in blobs.iter_mut() {
+                wrap(blob, width, height);
+            }
+        }
+        BoundaryMode::Absorb => {
+            blobs.retain(|blob| {
+                let inside = contains(&blob.center(), width, height);
+                if !inside {
+                    absorbed.push(blob.id);
+                }
+                inside
+            });
+        }
+        BoundaryMode::Unbounded => {}
+    }
+
+    absorbed
+}
+
+/// Push the blob's square back inside the world, pointing its velocity away
+/// from any edge it crossed.
+///
+/// The velocity is only ever pointed inwards rather than flipped, so a blob
+/// that is still outside after a tick doesn't turn back out again.
+pub fn reflect(blob: &mut Blob, restitution: f64, width: f64, height: f64) {
+    let max_x = (width - blob.size).max(0.0);
+    if blob.position.x < 0.0 {
+        blob.position.x = 0.0;
+        blob.velocity.x = blob.velocity.x.abs() * restitution;
+    } else if blob.position.x > max_x {
+        blob.position.x = max_x;
+        blob.velocity.x = -blob.velocity.x.abs() * restitution;
+    }
+
+    let max_y = (height - blob.size).max(0.0);
+    if blob.position.y < 0.0 {
+        blob.position.y = 0.0;
+        blob.velocity.y = blob.velocity.y.abs() * restitution;
+    } else if blob.position.y > max_y {
+        blob.position.y = max_y;
+        blob.velocity.y = -blob.velocity.y.abs() * restitution;
+    }
+}
+
+/// Move the blob back inside the world through the opposite edge.
+///
+/// The previous position moves with it, so the blob is drawn continuing
+/// across the edge instead of sweeping across the whole world.
+pub fn wrap(blob: &mut Blob, width: f64, height: f64) {
+    if width <= 0.0 || height <= 0.0 {
+        return;
+    }
+
+    let wrapped = Vector2f::new(blob.position.x.rem_euclid(width), blob.position.y.rem_euclid(height));
+    let shift = &wrapped - &blob.position;
+
+    blob.previous_position += shift;
+    blob.position = wrapped;
+}
+
+/// Where `other` is closest to `position` in a world that wraps around at
+/// `width` and `height`.
+pub fn nearest_image(position: &Vector2f, other: &Vector2f, width: f64, height: f64) -> Vector2f {
+    let nearest = |value: f64, other: f64, extent: f64| {
+        if extent > 0.0 {
+            other - extent * ((other - value) / extent).round()
+        } else {
+            other
+        }
+    };
+
+    Vector2f::new(
+        nearest(position.x, other.x, width),
+        nearest(position.y, other.y, height),
+    )
+}
+
+fn contains(point: &Vector2f, width: f64, height: f64) -> bool {
+    point.x >= 0.0 && point.x <= width && point.y >= 0.0 && point.y <= height
+}
@@ -0,0 +1,178 @@
+use crate::{
+    app::{Blob, SpatialHash},
+    common::{
+        config::{CollisionConfig, CollisionShape},
+        math::Vector2f,
+    },
+};
+
+/// How two overlapping blobs touch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contact {
+    /// Unit vector pointing from the first blob towards the second.
+    pub normal: Vector2f,
+    /// How far the blobs overlap along `normal`.
+    pub depth: f64,
+}
+
+/// Where the circles drawn for `a` and `b` overlap, if they do.
+pub fn circle_contact(a: &Blob, b: &Blob) -> Option<Contact> {
+    let offset = &b.center() - &a.center();
+    let distance = offset.magnitude();
+
+    let depth = a.radius() + b.radius() - distance;
+    if depth <= 0.0 {
+        return None;
+    }
+
+    // Blobs sitting exactly on top of each other have no direction to separate
+    // in, so pick one
+    let normal = if distance > 0.0 {
+        offset / distance
+    } else {
+        Vector2f::new(1.0, 0.0)
+    };
+
+    Some(Contact { normal, depth })
+}
+
+/// Where the squares around `a` and `b` overlap, if they do. The blobs are
+/// separated along whichever axis they overlap least on.
+pub fn aabb_contact(a: &Blob, b: &Blob) -> Option<Contact> {
+    let offset = &b.center() - &a.center();
+    let overlap_x = a.radius() + b.radius() - offset.x.abs();
+    let overlap_y = a.radius() + b.radius() - offset.y.abs();
+
+    if overlap_x <= 0.0 || overlap_y <= 0.0 {
+        return None;
+    }
+
+    let contact = if overlap_x < overlap_y {
+        Contact {
+            normal: Vector2f::new(offset.x.signum(), 0.0),
+            depth: overlap_x,
+        }
+    } else {
+        Contact {
+            normal: Vector2f::new(0.0, offset.y.signum()),
+            depth: overlap_y,
+        }
+    };
+
+    Some(contact)
+}
+
+pub fn contact(shape: CollisionShape, a: &Blob, b: &Blob) -> Option<Contact> {
+    match shape {
+        CollisionShape::Circle => circle_contact(a, b),
+        CollisionShape::Aabb => aabb_contact(a, b),
+    }
+}
+
+/// Push `a` and `b` apart until they just touch, then exchange an impulse
+/// between them along `contact.normal`, with Coulomb friction along the
+/// surface. Heavier blobs move less.
+pub fn resolve(a: &mut Blob, b: &mut Blob, contact: &Contact, restitution: f64, friction: f64) {
+    let inverse_a = 1.0 / a.mass;
+    let inverse_b = 1.0 / b.mass;
+    let inverse_total = inverse_a + inverse_b;
+    if !inverse_total.is_finite() {
+        return;
+    }
+
+    // Move where they were last tick too, so interpolating between the two
+    // doesn't show the jump
+    let correction = &contact.normal * (contact.depth / inverse_total);
+    let (correction_a, correction_b) = (&correction * inverse_a, &correction * inverse_b);
+    a.position -= &correction_a;
+    a.previous_position -= &correction_a;
+    b.position += &correction_b;
+    b.previous_position += &correction_b;
+
+    let relative = &b.velocity - &a.velocity;
+    let normal_speed = relative.dot(&contact.normal);
+
+    // Already moving apart
+    if normal_speed >= 0.0 {
+        return;
+    }
+
+    let impulse = -(1.0 + restitution) * normal_speed / inverse_total;
+    a.velocity -= &contact.normal * (impulse * inverse_a);
+    b.velocity += &contact.normal * (impulse * inverse_b);
+
+    let tangent = &relative - &contact.normal * normal_speed;
+    let tangent_speed = tangent.magnitude();
+    if tangent_speed > 0.0 {
+        // Never more than enough to stop them sliding
+        let friction_impulse = (tangent_speed / inverse_total).min(friction * impulse);
+        let tangent = tangent / tangent_speed;
+
+        a.velocity += &tangent * (friction_impulse * inverse_a);
+        b.velocity -= &tangent * (friction_impulse * inverse_b);
+    }
+}
+
+/// Find and resolve every pair of overlapping blobs, returning how many there
+/// were. With `wrap`, blobs also collide across opposite edges.
+///
+/// Pairs are found through `spatial_hash` with cells as large as the largest
+/// blob, then resolved one after another in a fixed order.
+pub fn resolve_collisions(
+    blobs: &mut [Blob],
+    spatial_hash: &mut SpatialHash,
+    width: f64,
+    height: f64,
+    wrap: bool,
+    config: &CollisionConfig,
+) -> usize {
+    let max_size = blobs.iter().map(|blob| blob.size).fold(0.0, f64::max);
+    if max_size <= 0.0 {
+        return 0;
+    }
+
+    spatial_hash.rebuild(width, height, max_size, blobs);
+
+    // Each pair along with the offset to the second blob's image nearest the first
+    let mut pairs = Vec::new();
+    for (index, blob) in blobs.iter().enumerate() {
+        if wrap {
+            spatial_hash.for_each_neighbor_wrapped(&blob.position, blobs, width, height, |other, offset| {
+                if other > index {
+                    pairs.push((index, other, offset.clone()));
+                }
+            });
+        } else {
+            spatial_hash.for_each_neighbor(&blob.position, |other| {
+                if other > index {
+                    pairs.push((index, other, Vector2f::ZERO));
+                }
+            });
+        }
+    }
+
+    let mut collisions = 0;
+    for (first, second, offset) in pairs {
+        let (left, right) = blobs.split_at_mut(second);
+        let (a, b) = (&mut left[first], &mut right[0]);
+
+        let found = if offset == Vector2f::ZERO {
+            contact(config.shape, a, b)
+        } else {
+            let image = Blob {
+                position: &b.position + &offset,
+                ..b.clone()
+            };
+            contact(config.shape, a, &image)
+        };
+
+        // Only the offset between the blobs matters, so the contact with the
+        // image applies to the blob itself
+        if let Some(contact) = found {
+            resolve(a, b, &contact, config.restitution, config.friction);
+            collisions += 1;
+        }
+    }
+
+    collisions
+}
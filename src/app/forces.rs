@@ -0,0 +1,163 @@
+pub mod boids;
+pub mod drag;
+pub mod gravity;
+pub mod lennard_jones;
+pub mod radial;
+pub mod species;
+pub mod wind;
+
+use crate::{
+    app::Blob,
+    common::{
+        config::{ForceConfig, PhysicsConfig},
+        math::Vector2f,
+    },
+};
+
+pub use boids::{Alignment, Cohesion, Separation};
+pub use drag::Drag;
+pub use gravity::Gravity;
+pub use lennard_jones::LennardJones;
+pub use radial::{Attraction, Repulsion};
+pub use species::SpeciesInteraction;
+pub use wind::Wind;
+
+/// Something that pushes blobs around.
+///
+/// Pairwise forces depend on the blobs around each blob and say how far they
+/// reach through `range`. Global forces return `None` from `range` and only
+/// ever see the blob itself.
+pub trait Force {
+    fn name(&self) -> &str;
+
+    /// How close other blobs have to be to affect a blob, or `None` if they
+    /// never do.
+    fn range(&self) -> Option<f64>;
+
+    /// The force on `blob`. `neighbors` holds at least every other blob within
+    /// `range`, possibly more, and is always empty for global forces.
+    fn force(&self, blob: &Blob, neighbors: &[&Blob]) -> Vector2f;
+}
+
+/// Sum `f` over every neighbor strictly within `range` and not on top of
+/// `blob`, passing the vector from the neighbor to `blob` and its length.
+#[inline]
+pub fn sum_pairwise(
+    blob: &Blob,
+    neighbors: &[&Blob],
+    range: f64,
+    mut f: impl FnMut(&Blob, Vector2f, f64) -> Vector2f,
+) -> Vector2f {
+    let mut total = Vector2f::ZERO;
+
+    for other in neighbors {
+        let offset = blob.position.vector_to(&other.position);
+        let distance = offset.magnitude();
+
+        // Blobs sitting exactly on top of each other have no direction to push in
+        if distance > 0.0 && distance <= range {
+            total += f(other, offset, distance);
+        }
+    }
+
+    total
+}
+
+pub fn build_force(config: &ForceConfig) -> Box<dyn Force> {
+    match *config {
+        ForceConfig::Repulsion { strength, range } => Box::new(Repulsion { strength, range }),
+        ForceConfig::Attraction { strength, range } => Box::new(Attraction { strength, range }),
+        ForceConfig::LennardJones { epsilon, sigma, range } => Box::new(LennardJones { epsilon, sigma, range }),
+        ForceConfig::Gravity { x, y, strength } => Box::new(Gravity {
+            center: Vector2f::new(x, y),
+            strength,
+        }),
+        ForceConfig::Alignment { strength, range } => Box::new(Alignment { strength, range }),
+        ForceConfig::Cohesion { strength, range } => Box::new(Cohesion { strength, range }),
+        ForceConfig::Separation { strength, range } => Box::new(Separation { strength, range }),
+        ForceConfig::Wind { x, y } => Box::new(Wind {
+            force: Vector2f::new(x, y),
+        }),
+        ForceConfig::Drag { coefficient } => Box::new(Drag { coefficient }),
+    }
+}
+
+/// Every force acting on a `World`: the ones described by its config, rebuilt
+/// whenever the config changes, plus any added in code.
+#[derive(Default)]
+pub struct ForceSet {
+    built_from: Option<PhysicsConfig>,
+    configured: Vec<Box<dyn Force>>,
+    custom: Vec<Box<dyn Force>>,
+}
+
+impl ForceSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rebuild the configured forces if `config` changed since the last call.
+    pub fn update_config(&mut self, config: &PhysicsConfig) {
+        if self.built_from.as_ref() == Some(config) {
+            return;
+        }
+
+        self.configured.clear();
+        if config.repel_force != 0.0 {
+            self.configured.push(Box::new(Repulsion {
+                strength: config.repel_force,
+                range: config.repel_distance,
+            }));
+        }
+        if config.friction_force != 0.0 {
+            self.configured.push(Box::new(Drag {
+                coefficient: config.friction_force,
+            }));
+        }
+        if let Some(interaction) = SpeciesInteraction::from_config(config) {
+            self.configured.push(Box::new(interaction));
+        }
+        self.configured.extend(config.forces.iter().map(build_force));
+
+        self.built_from = Some(config.clone());
+    }
+
+    /// Add a force that isn't described by the config. It stays until removed,
+    /// whatever happens to the config.
+    pub fn add(&mut self, force: Box<dyn Force>) {
+        self.custom.push(force);
+    }
+
+    /// Remove every force added with [`ForceSet::add`] called `name`.
+    pub fn remove(&mut self, name: &str) -> usize {
+        let before = self.custom.len();
+        self.custom.retain(|force| force.name() != name);
+        before - self.custom.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Force> {
+        self.configured.iter().chain(&self.custom).map(|force| force.as_ref())
+    }
+
+    /// The total of every force on `blob`. Global forces are not shown the neighbors.
+    #[inline]
+    pub fn force_on(&self, blob: &Blob, neighbors: &[&Blob]) -> Vector2f {
+        let mut total = Vector2f::ZERO;
+
+        for force in self.iter() {
+            total += match force.range() {
+                Some(_) => force.force(blob, neighbors),
+                None => force.force(blob, &[]),
+            };
+        }
+
+        total
+    }
+
+    /// The furthest any force reaches, or `None` if they are all global.
+    pub fn max_range(&self) -> Option<f64> {
+        self.iter()
+            .filter_map(|force| force.range())
+            .fold(None, |max, range| Some(max.map_or(range, |max: f64| max.max(range))))
+    }
+}
@@ -0,0 +1,95 @@
+//! The three steering rules from Craig Reynolds' boids. Together they make
+//! blobs flock; each is useful on its own too.
+
+use crate::{
+    app::{forces::sum_pairwise, Blob, Force},
+    common::math::Vector2f,
+};
+
+/// Steers towards the average velocity of the blobs within `range`.
+#[derive(Debug, Clone)]
+pub struct Alignment {
+    pub strength: f64,
+    pub range: f64,
+}
+
+impl Force for Alignment {
+    fn name(&self) -> &str {
+        "alignment"
+    }
+
+    fn range(&self) -> Option<f64> {
+        Some(self.range)
+    }
+
+    fn force(&self, blob: &Blob, neighbors: &[&Blob]) -> Vector2f {
+        let mut count = 0;
+        let total = sum_pairwise(blob, neighbors, self.range, |other, _, _| {
+            count += 1;
+            other.velocity.clone()
+        });
+
+        if count == 0 {
+            return Vector2f::ZERO;
+        }
+
+        let average = total / count as f64;
+        (&average - &blob.velocity) * self.strength
+    }
+}
+
+/// Steers towards the center of the blobs within `range`.
+#[derive(Debug, Clone)]
+pub struct Cohesion {
+    pub strength: f64,
+    pub range: f64,
+}
+
+impl Force for Cohesion {
+    fn name(&self) -> &str {
+        "cohesion"
+    }
+
+    fn range(&self) -> Option<f64> {
+        Some(self.range)
+    }
+
+    fn force(&self, blob: &Blob, neighbors: &[&Blob]) -> Vector2f {
+        let mut count = 0;
+        let total = sum_pairwise(blob, neighbors, self.range, |other, _, _| {
+            count += 1;
+            other.position.clone()
+        });
+
+        if count == 0 {
+            return Vector2f::ZERO;
+        }
+
+        let center = total / count as f64;
+        (&center - &blob.position) * self.strength
+    }
+}
+
+/// Steers away from the blobs within `range`, in inverse proportion to how
+/// far away they are.
+#[derive(Debug, Clone)]
+pub struct Separation {
+    pub strength: f64,
+    pub range: f64,
+}
+
+impl Force for Separation {
+    fn name(&self) -> &str {
+        "separation"
+    }
+
+    fn range(&self) -> Option<f64> {
+        Some(self.range)
+    }
+
+    fn force(&self, blob: &Blob, neighbors: &[&Blob]) -> Vector2f {
+        sum_pairwise(blob, neighbors, self.range, |_, offset, distance| {
+            offset * (self.strength / (distance * distance))
+        })
+    }
+}
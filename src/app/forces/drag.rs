@@ -0,0 +1,24 @@
+use crate::{
+    app::{Blob, Force},
+    common::math::Vector2f,
+};
+
+/// Slows blobs down in proportion to their velocity.
+#[derive(Debug, Clone)]
+pub struct Drag {
+    pub coefficient: f64,
+}
+
+impl Force for Drag {
+    fn name(&self) -> &str {
+        "drag"
+    }
+
+    fn range(&self) -> Option<f64> {
+        None
+    }
+
+    fn force(&self, blob: &Blob, _neighbors: &[&Blob]) -> Vector2f {
+        blob.velocity.clone() * -self.coefficient
+    }
+}
@@ -0,0 +1,31 @@
+use crate::{
+    app::{Blob, Force},
+    common::math::Vector2f,
+};
+
+/// Pulls every blob towards `center` with a constant `strength`, however far
+/// away it is.
+#[derive(Debug, Clone)]
+pub struct Gravity {
+    pub center: Vector2f,
+    pub strength: f64,
+}
+
+impl Force for Gravity {
+    fn name(&self) -> &str {
+        "gravity"
+    }
+
+    fn range(&self) -> Option<f64> {
+        None
+    }
+
+    fn force(&self, blob: &Blob, _neighbors: &[&Blob]) -> Vector2f {
+        let offset = self.center.vector_to(&blob.position);
+        if offset.magnitude() == 0.0 {
+            return Vector2f::ZERO;
+        }
+
+        self.strength * offset.normalized()
+    }
+}
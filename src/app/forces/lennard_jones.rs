@@ -0,0 +1,36 @@
+use crate::{
+    app::{forces::sum_pairwise, Blob, Force},
+    common::math::Vector2f,
+};
+
+/// The Lennard-Jones potential: strongly repulsive closer than `sigma`,
+/// weakly attractive further out, so blobs settle into loose lattices.
+///
+/// `epsilon` is the depth of the potential well. Distances are clamped to at
+/// least half of `sigma` so blobs that overlap don't fling each other away.
+#[derive(Debug, Clone)]
+pub struct LennardJones {
+    pub epsilon: f64,
+    pub sigma: f64,
+    pub range: f64,
+}
+
+impl Force for LennardJones {
+    fn name(&self) -> &str {
+        "lennard_jones"
+    }
+
+    fn range(&self) -> Option<f64> {
+        Some(self.range)
+    }
+
+    fn force(&self, blob: &Blob, neighbors: &[&Blob]) -> Vector2f {
+        sum_pairwise(blob, neighbors, self.range, |_, offset, distance| {
+            let distance = distance.max(self.sigma / 2.0);
+            let ratio6 = (self.sigma / distance).powi(6);
+            let magnitude = 24.0 * self.epsilon / distance * (2.0 * ratio6 * ratio6 - ratio6);
+
+            magnitude * offset.normalized()
+        })
+    }
+}
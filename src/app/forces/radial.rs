@@ -0,0 +1,50 @@
+use crate::{
+    app::{forces::sum_pairwise, Blob, Force},
+    common::math::Vector2f,
+};
+
+/// Pushes away from every blob within `range` with a constant `strength`.
+#[derive(Debug, Clone)]
+pub struct Repulsion {
+    pub strength: f64,
+    pub range: f64,
+}
+
+impl Force for Repulsion {
+    fn name(&self) -> &str {
+        "repulsion"
+    }
+
+    fn range(&self) -> Option<f64> {
+        Some(self.range)
+    }
+
+    fn force(&self, blob: &Blob, neighbors: &[&Blob]) -> Vector2f {
+        sum_pairwise(blob, neighbors, self.range, |_, offset, _| {
+            self.strength * offset.normalized()
+        })
+    }
+}
+
+/// Pulls towards every blob within `range` with a constant `strength`.
+#[derive(Debug, Clone)]
+pub struct Attraction {
+    pub strength: f64,
+    pub range: f64,
+}
+
+impl Force for Attraction {
+    fn name(&self) -> &str {
+        "attraction"
+    }
+
+    fn range(&self) -> Option<f64> {
+        Some(self.range)
+    }
+
+    fn force(&self, blob: &Blob, neighbors: &[&Blob]) -> Vector2f {
+        sum_pairwise(blob, neighbors, self.range, |_, offset, _| {
+            -self.strength * offset.normalized()
+        })
+    }
+}
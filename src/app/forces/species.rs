@@ -0,0 +1,57 @@
+use crate::{
+    app::{forces::sum_pairwise, Blob, Force},
+    common::{config::PhysicsConfig, math::Vector2f},
+};
+
+/// Pulls or pushes blobs depending on their species, as set by each
+/// species' `interactions`. The push or pull fades out linearly towards `range`.
+#[derive(Debug, Clone)]
+pub struct SpeciesInteraction {
+    pub range: f64,
+    /// `strengths[a][b]` is how strongly species `a` is pulled towards `b`.
+    pub strengths: Vec<Vec<f64>>,
+}
+
+impl SpeciesInteraction {
+    /// `None` if no species is pulled or pushed by any other.
+    pub fn from_config(config: &PhysicsConfig) -> Option<Self> {
+        let strengths: Vec<Vec<f64>> = config
+            .species
+            .iter()
+            .map(|species| species.interactions.clone())
+            .collect();
+
+        if strengths.iter().flatten().all(|&strength| strength == 0.0) {
+            return None;
+        }
+
+        Some(Self {
+            range: config.interaction_range,
+            strengths,
+        })
+    }
+}
+
+impl Force for SpeciesInteraction {
+    fn name(&self) -> &str {
+        "species"
+    }
+
+    fn range(&self) -> Option<f64> {
+        Some(self.range)
+    }
+
+    fn force(&self, blob: &Blob, neighbors: &[&Blob]) -> Vector2f {
+        let strengths = match self.strengths.get(blob.species) {
+            Some(strengths) => strengths,
+            None => return Vector2f::ZERO,
+        };
+
+        sum_pairwise(blob, neighbors, self.range, |other, offset, distance| {
+            let strength = strengths.get(other.species).copied().unwrap_or(0.0);
+
+            // `offset` points away from `other`, so positive strengths pull against it
+            -strength * (1.0 - distance / self.range) * offset.normalized()
+        })
+    }
+}
@@ -0,0 +1,24 @@
+use crate::{
+    app::{Blob, Force},
+    common::math::Vector2f,
+};
+
+/// The same push on every blob.
+#[derive(Debug, Clone)]
+pub struct Wind {
+    pub force: Vector2f,
+}
+
+impl Force for Wind {
+    fn name(&self) -> &str {
+        "wind"
+    }
+
+    fn range(&self) -> Option<f64> {
+        None
+    }
+
+    fn force(&self, _blob: &Blob, _neighbors: &[&Blob]) -> Vector2f {
+        self.force.clone()
+    }
+}
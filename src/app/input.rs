@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{app::BlobId, common::config::PhysicsConfig};
+
+/// Anything from outside the simulation that changes a `World`.
+///
+/// Inputs are applied between ticks so that replaying the same inputs at the
+/// same ticks reproduces the same world.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum WorldInput {
+    AddBlob {
+        x: f64,
+        y: f64,
+        #[serde(default)]
+        species: usize,
+    },
+    AddRandomBlobs { count: usize },
+    RemoveBlob { id: BlobId },
+    /// Hold a blob still with its top left corner at `x`, `y`.
+    MoveBlob { id: BlobId, x: f64, y: f64 },
+    SetBlobVelocity { id: BlobId, x: f64, y: f64 },
+    /// Push every blob within `radius` of `x`, `y` away from it, or pull them
+    /// in if `strength` is negative. See [`World::push_blobs`](crate::app::World::push_blobs).
+    PushBlobs { x: f64, y: f64, radius: f64, strength: f64 },
+    /// Replace the physics config, such as when the config file is reloaded.
+    SetConfig { config: PhysicsConfig },
+    /// Change the size of the world, such as when the window size in the
+    /// config changes.
+    Resize { width: usize, height: usize },
+}
+
+/// Every input applied to a `World`, keyed by the tick it was applied before.
+#[derive(Debug, Clone, Default)]
+pub struct InputLog {
+    entries: Vec<(u64, WorldInput)>,
+}
+
+impl InputLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Entries must be pushed in tick order.
+    pub fn push(&mut self, tick: u64, input: WorldInput) {
+        debug_assert!(self.entries.last().map_or(0, |(last, _)| *last) <= tick);
+        self.entries.push((tick, input));
+    }
+
+    pub fn inputs_at(&self, tick: u64) -> impl Iterator<Item = &WorldInput> {
+        let start = self.entries.partition_point(|(t, _)| *t < tick);
+        self.entries[start..]
+            .iter()
+            .take_while(move |(t, _)| *t == tick)
+            .map(|(_, input)| input)
+    }
+
+    pub fn entries(&self) -> &[(u64, WorldInput)] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
@@ -0,0 +1,40 @@
+pub mod euler;
+pub mod rk4;
+pub mod verlet;
+
+use crate::{
+    app::Blob,
+    common::{config::IntegratorKind, math::Vector2f},
+};
+
+pub use euler::{ExplicitEuler, SemiImplicitEuler};
+pub use rk4::Rk4;
+pub use verlet::VelocityVerlet;
+
+/// Moves blobs forward in time from their positions, velocities and the
+/// forces acting on them.
+pub trait Integrator {
+    fn name(&self) -> &str;
+
+    /// Move `blobs` forward by `delta_time`, leaving the acceleration used in
+    /// each blob's `acceleration`.
+    ///
+    /// `accelerations` fills its second argument with the acceleration of every
+    /// blob in its first. It may be called more than once per step, with
+    /// blobs at intermediate positions and velocities.
+    fn step(
+        &mut self,
+        blobs: &mut [Blob],
+        delta_time: f64,
+        accelerations: &mut dyn FnMut(&[Blob], &mut Vec<Vector2f>),
+    );
+}
+
+pub fn build_integrator(kind: IntegratorKind) -> Box<dyn Integrator> {
+    match kind {
+        IntegratorKind::ExplicitEuler => Box::new(ExplicitEuler::default()),
+        IntegratorKind::SemiImplicitEuler => Box::new(SemiImplicitEuler::default()),
+        IntegratorKind::VelocityVerlet => Box::new(VelocityVerlet::default()),
+        IntegratorKind::Rk4 => Box::new(Rk4::default()),
+    }
+}
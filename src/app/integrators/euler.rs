@@ -0,0 +1,58 @@
+use crate::{
+    app::{Blob, Integrator},
+    common::math::Vector2f,
+};
+
+/// Moves with the old velocity, then accelerates.
+#[derive(Default)]
+pub struct ExplicitEuler {
+    accelerations: Vec<Vector2f>,
+}
+
+impl Integrator for ExplicitEuler {
+    fn name(&self) -> &str {
+        "explicit_euler"
+    }
+
+    fn step(
+        &mut self,
+        blobs: &mut [Blob],
+        delta_time: f64,
+        accelerations: &mut dyn FnMut(&[Blob], &mut Vec<Vector2f>),
+    ) {
+        accelerations(blobs, &mut self.accelerations);
+
+        for (blob, acceleration) in blobs.iter_mut().zip(&self.accelerations) {
+            blob.position += &blob.velocity * delta_time;
+            blob.velocity += acceleration * delta_time;
+            blob.acceleration = acceleration.clone();
+        }
+    }
+}
+
+/// Accelerates, then moves with the new velocity.
+#[derive(Default)]
+pub struct SemiImplicitEuler {
+    accelerations: Vec<Vector2f>,
+}
+
+impl Integrator for SemiImplicitEuler {
+    fn name(&self) -> &str {
+        "semi_implicit_euler"
+    }
+
+    fn step(
+        &mut self,
+        blobs: &mut [Blob],
+        delta_time: f64,
+        accelerations: &mut dyn FnMut(&[Blob], &mut Vec<Vector2f>),
+    ) {
+        accelerations(blobs, &mut self.accelerations);
+
+        for (blob, acceleration) in blobs.iter_mut().zip(&self.accelerations) {
+            blob.velocity += acceleration * delta_time;
+            blob.position += &blob.velocity * delta_time;
+            blob.acceleration = acceleration.clone();
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use crate::{
+    app::{Blob, Integrator},
+    common::math::Vector2f,
+};
+
+/// Classic fourth order Runge-Kutta over every blob's position and velocity.
+#[derive(Default)]
+pub struct Rk4 {
+    /// The blobs at each intermediate stage.
+    stage: Vec<Blob>,
+    velocities: [Vec<Vector2f>; 4],
+    accelerations: [Vec<Vector2f>; 4],
+}
+
+impl Integrator for Rk4 {
+    fn name(&self) -> &str {
+        "rk4"
+    }
+
+    fn step(
+        &mut self,
+        blobs: &mut [Blob],
+        delta_time: f64,
+        accelerations: &mut dyn FnMut(&[Blob], &mut Vec<Vector2f>),
+    ) {
+        self.stage.clear();
+        self.stage.extend_from_slice(blobs);
+
+        self.velocities[0].clear();
+        self.velocities[0].extend(blobs.iter().map(|blob| blob.velocity.clone()));
+        accelerations(blobs, &mut self.accelerations[0]);
+
+        for k in 1..4 {
+            let h = if k == 3 { delta_time } else { delta_time / 2.0 };
+
+            for (index, (stage, blob)) in self.stage.iter_mut().zip(blobs.iter()).enumerate() {
+                stage.position = &blob.position + &self.velocities[k - 1][index] * h;
+                stage.velocity = &blob.velocity + &self.accelerations[k - 1][index] * h;
+            }
+
+            self.velocities[k].clear();
+            self.velocities[k].extend(self.stage.iter().map(|blob| blob.velocity.clone()));
+            accelerations(&self.stage, &mut self.accelerations[k]);
+        }
+
+        for (index, blob) in blobs.iter_mut().enumerate() {
+            let velocity = weighted_sum(&self.velocities, index);
+            let acceleration = weighted_sum(&self.accelerations, index);
+
+            blob.position += velocity * delta_time;
+            blob.velocity += &acceleration * delta_time;
+            blob.acceleration = acceleration;
+        }
+    }
+}
+
+/// `(k1 + 2 k2 + 2 k3 + k4) / 6` for one blob.
+#[inline]
+fn weighted_sum(k: &[Vec<Vector2f>; 4], index: usize) -> Vector2f {
+    let [k1, k2, k3, k4] = k;
+    let (k1, k2, k3, k4) = (&k1[index], &k2[index], &k3[index], &k4[index]);
+
+    Vector2f::new(
+        (k1.x + 2.0 * (k2.x + k3.x) + k4.x) / 6.0,
+        (k1.y + 2.0 * (k2.y + k3.y) + k4.y) / 6.0,
+    )
+}
@@ -0,0 +1,39 @@
+use crate::{
+    app::{Blob, Integrator},
+    common::math::Vector2f,
+};
+
+/// Velocity Verlet, reusing the acceleration left in each blob by the previous
+/// step so forces are only evaluated once per step.
+///
+/// Forces that depend on velocity, like drag, see the velocity from the start
+/// of the step.
+#[derive(Default)]
+pub struct VelocityVerlet {
+    accelerations: Vec<Vector2f>,
+}
+
+impl Integrator for VelocityVerlet {
+    fn name(&self) -> &str {
+        "velocity_verlet"
+    }
+
+    fn step(
+        &mut self,
+        blobs: &mut [Blob],
+        delta_time: f64,
+        accelerations: &mut dyn FnMut(&[Blob], &mut Vec<Vector2f>),
+    ) {
+        for blob in blobs.iter_mut() {
+            blob.position += &blob.velocity * delta_time;
+            blob.position += &blob.acceleration * (0.5 * delta_time * delta_time);
+        }
+
+        accelerations(blobs, &mut self.accelerations);
+
+        for (blob, acceleration) in blobs.iter_mut().zip(&self.accelerations) {
+            blob.velocity += (&blob.acceleration + acceleration) * (0.5 * delta_time);
+            blob.acceleration = acceleration.clone();
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+
+use crate::{
+    app::{recording::BlobState, Blob, BlobId, RecordedFrame, Recording},
+    common::math,
+    graphics::Instance,
+};
+
+const MIN_SPEED: f64 = 1.0 / 16.0;
+const MAX_SPEED: f64 = 16.0;
+
+/// Plays a `Recording` back, with pausing, seeking and speed control.
+///
+/// The playhead is measured in frames and can sit between two of them, in
+/// which case blobs are drawn interpolated between the two.
+pub struct Playback {
+    recording: Recording,
+    position: f64,
+    speed: f64,
+    paused: bool,
+}
+
+impl Playback {
+    pub fn new(recording: Recording) -> Self {
+        Self {
+            recording,
+            position: 0.0,
+            speed: 1.0,
+            paused: false,
+        }
+    }
+
+    pub fn recording(&self) -> &Recording {
+        &self.recording
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.recording.frames.len()
+    }
+
+    /// The index of the frame at or just before the playhead.
+    pub fn frame_index(&self) -> usize {
+        self.position as usize
+    }
+
+    pub fn current_frame(&self) -> Option<&RecordedFrame> {
+        self.recording.frames.get(self.frame_index())
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    /// Negative speeds are not supported; step backwards instead.
+    pub fn set_speed(&mut self, speed: f64) {
+        self.speed = math::clamp(speed, MIN_SPEED, MAX_SPEED);
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    /// Move the playhead by `delta_time` seconds of recorded time, scaled by
+    /// the playback speed. Pauses at the last frame.
+    pub fn update(&mut self, delta_time: f64) {
+        if self.paused {
+            return;
+        }
+
+        self.position += delta_time / self.recording.header.step * self.speed;
+        if self.position >= self.last_frame() as f64 {
+            self.position = self.last_frame() as f64;
+            self.paused = true;
+        }
+    }
+
+    /// Jump to `frame`, clamped to the recording.
+    pub fn seek(&mut self, frame: usize) {
+        self.position = frame.min(self.last_frame()) as f64;
+    }
+
+    /// Pause, then move whole frames forwards or backwards.
+    pub fn step(&mut self, frames: isize) {
+        self.paused = true;
+
+        let frame = self.frame_index() as isize + frames;
+        self.seek(frame.max(0) as usize);
+    }
+
+    /// An instance for every blob in the current frame, interpolated towards
+    /// the next frame by how far the playhead is between them.
+    pub fn instances(&self) -> Vec<Instance> {
+        let frames = &self.recording.frames;
+        let index = self.frame_index();
+        let current = match frames.get(index) {
+            Some(frame) => frame,
+            None => return Vec::new(),
+        };
+
+        let alpha = self.position - index as f64;
+        let config = &self.recording.header.config;
+        let next: HashMap<BlobId, &BlobState> = frames
+            .get(index + 1)
+            .filter(|_| alpha > 0.0)
+            .map(|frame| frame.blobs.iter().map(|blob| (blob.id, blob)).collect())
+            .unwrap_or_default();
+
+        current
+            .blobs
+            .iter()
+            .map(|state| {
+                let target = next.get(&state.id).copied().unwrap_or(state);
+
+                let blob = Blob {
+                    id: state.id,
+                    species: state.species,
+                    size: state.size,
+                    previous_position: state.position.clone(),
+                    position: target.position.clone(),
+                    velocity: state.velocity.clone(),
+                    ..Default::default()
+                };
+                blob.to_instance(alpha, config)
+            })
+            .collect()
+    }
+
+    fn last_frame(&self) -> usize {
+        self.recording.frames.len().saturating_sub(1)
+    }
+}
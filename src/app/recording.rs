@@ -0,0 +1,335 @@
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
+use std::{convert::TryInto, path::Path};
+
+use crate::{
+    app::{Blob, BlobId, InputLog, World, WorldInput},
+    common::{config::PhysicsConfig, math::Vector2f},
+};
+
+/// Bumped whenever the recording file layout changes. Version 4 added the
+/// `SetConfig` and `Resize` inputs.
+pub const RECORDING_VERSION: u32 = 4;
+
+/// Recordings start with this, followed by the version as a little endian `u32`.
+const RECORDING_MAGIC: &[u8; 8] = b"BLOBREC\0";
+
+/// What the recorded world looked like before its first frame.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordingHeader {
+    pub width: usize,
+    pub height: usize,
+    pub seed: u64,
+    /// Seconds of simulation per recorded tick.
+    pub step: f64,
+    pub config: PhysicsConfig,
+}
+
+impl RecordingHeader {
+    /// The world as it was when recording started, if it was recorded from
+    /// empty with every blob added by an input.
+    pub fn world(&self) -> World {
+        World::with_seed(self.width, self.height, self.config.clone(), self.seed)
+    }
+}
+
+/// The parts of a blob needed to draw and inspect it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlobState {
+    pub id: BlobId,
+    pub species: usize,
+    pub size: f64,
+    pub position: Vector2f,
+    pub velocity: Vector2f,
+}
+
+impl From<&Blob> for BlobState {
+    fn from(blob: &Blob) -> Self {
+        Self {
+            id: blob.id,
+            species: blob.species,
+            size: blob.size,
+            position: blob.position.clone(),
+            velocity: blob.velocity.clone(),
+        }
+    }
+}
+
+impl BlobState {
+    const EMPTY: BlobState = BlobState {
+        id: BlobId::new(0),
+        species: 0,
+        size: 0.0,
+        position: Vector2f::ZERO,
+        velocity: Vector2f::ZERO,
+    };
+}
+
+/// Every blob after one tick, and the inputs applied just before it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedFrame {
+    pub tick: u64,
+    pub inputs: Vec<WorldInput>,
+    pub blobs: Vec<BlobState>,
+}
+
+/// A fully decoded recording, ready to play back.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recording {
+    pub header: RecordingHeader,
+    pub frames: Vec<RecordedFrame>,
+}
+
+/// Records a `World` tick by tick, encoding each frame as it goes.
+///
+/// Each value is stored as the XOR of its bits with the same value in the
+/// previous frame, written as a varint. Values that didn't change take one
+/// byte and values that changed a little only a few, while playback is still
+/// bit for bit exact.
+pub struct Recorder {
+    header: RecordingHeader,
+    frame_count: u64,
+    encoded: Vec<u8>,
+    previous_tick: u64,
+    previous: Vec<BlobState>,
+}
+
+impl Recorder {
+    /// Start recording `world`, taking its current state as the first frame.
+    pub fn new(world: &World, step: f64) -> Self {
+        let mut recorder = Self {
+            header: RecordingHeader {
+                width: world.width,
+                height: world.height,
+                seed: world.seed(),
+                step,
+                config: world.config.clone(),
+            },
+            frame_count: 0,
+            encoded: Vec::new(),
+            previous_tick: world.tick(),
+            previous: Vec::new(),
+        };
+        recorder.record(world, &[]);
+
+        recorder
+    }
+
+    /// Record `world` as it is after a tick, along with the inputs that were
+    /// applied before that tick.
+    pub fn record(&mut self, world: &World, inputs: &[WorldInput]) {
+        let encoded = &mut self.encoded;
+
+        write_varint(encoded, world.tick() - self.previous_tick);
+        self.previous_tick = world.tick();
+
+        write_varint(encoded, inputs.len() as u64);
+        for input in inputs {
+            let bytes = bincode::serialize(input).expect("WorldInput always serializes");
+            write_varint(encoded, bytes.len() as u64);
+            encoded.extend_from_slice(&bytes);
+        }
+
+        write_varint(encoded, world.blobs.len() as u64);
+        for (index, blob) in world.blobs.iter().enumerate() {
+            let previous = self.previous.get(index).unwrap_or(&BlobState::EMPTY);
+
+            write_varint(encoded, blob.id.get() ^ previous.id.get());
+            write_varint(encoded, (blob.species ^ previous.species) as u64);
+            for (value, previous) in blob_values(&BlobState::from(blob))
+                .iter()
+                .zip(&blob_values(previous))
+            {
+                write_varint(encoded, value.to_bits() ^ previous.to_bits());
+            }
+        }
+
+        self.previous.clear();
+        self.previous.extend(world.blobs.iter().map(BlobState::from));
+        self.frame_count += 1;
+    }
+
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let header = bincode::serialize(&self.header).expect("RecordingHeader always serializes");
+
+        let mut bytes = RECORDING_MAGIC.to_vec();
+        bytes.extend_from_slice(&RECORDING_VERSION.to_le_bytes());
+        write_varint(&mut bytes, header.len() as u64);
+        bytes.extend_from_slice(&header);
+        write_varint(&mut bytes, self.frame_count);
+        bytes.extend_from_slice(&self.encoded);
+
+        bytes
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_bytes()).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+impl Recording {
+    /// Every recorded input, keyed by the tick it was applied before, for
+    /// rerunning the simulation from [`RecordingHeader::world`].
+    pub fn input_log(&self) -> InputLog {
+        let mut log = InputLog::new();
+        for frame in &self.frames {
+            for input in &frame.inputs {
+                log.push(frame.tick.saturating_sub(1), input.clone());
+            }
+        }
+
+        log
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+
+        Self::from_bytes(&bytes).with_context(|| format!("Failed to load {}", path.display()))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        let header_length = RECORDING_MAGIC.len() + 4;
+        if bytes.len() < header_length || &bytes[..RECORDING_MAGIC.len()] != RECORDING_MAGIC {
+            bail!("Not a recording");
+        }
+
+        let version = u32::from_le_bytes(bytes[RECORDING_MAGIC.len()..header_length].try_into()?);
+        if version != RECORDING_VERSION {
+            bail!(
+                "Recording version {} can't be played back by version {}",
+                version,
+                RECORDING_VERSION
+            );
+        }
+
+        let mut reader = Reader {
+            bytes: &bytes[header_length..],
+        };
+
+        let length = reader.read_varint()? as usize;
+        let header = bincode::deserialize(reader.read_bytes(length)?)?;
+
+        let frame_count = reader.read_varint()?;
+        let mut frames: Vec<RecordedFrame> = Vec::new();
+        let mut tick = 0;
+
+        for _ in 0..frame_count {
+            tick += reader.read_varint()?;
+
+            // Every input starts with its length
+            let input_count = reader.read_count(1)?;
+            let mut inputs = Vec::new();
+            for _ in 0..input_count {
+                let length = reader.read_varint()? as usize;
+                inputs.push(bincode::deserialize(reader.read_bytes(length)?)?);
+            }
+
+            // An id, a species and each of the values, at least a byte each
+            let blob_count = reader.read_count(2 + BLOB_VALUES)?;
+            let previous_blobs = frames.last().map_or(&[][..], |frame| &frame.blobs[..]);
+            let mut blobs = Vec::with_capacity(blob_count);
+
+            for index in 0..blob_count {
+                let previous = previous_blobs.get(index).unwrap_or(&BlobState::EMPTY);
+
+                let id = BlobId::new(reader.read_varint()? ^ previous.id.get());
+                let species = (reader.read_varint()? as usize) ^ previous.species;
+                let mut values = blob_values(previous);
+                for value in &mut values {
+                    *value = f64::from_bits(reader.read_varint()? ^ value.to_bits());
+                }
+
+                let [size, x, y, vx, vy] = values;
+                blobs.push(BlobState {
+                    id,
+                    species,
+                    size,
+                    position: Vector2f::new(x, y),
+                    velocity: Vector2f::new(vx, vy),
+                });
+            }
+
+            frames.push(RecordedFrame { tick, inputs, blobs });
+        }
+
+        if !reader.bytes.is_empty() {
+            bail!("{} unexpected bytes after the last frame", reader.bytes.len());
+        }
+
+        Ok(Self { header, frames })
+    }
+}
+
+/// How many `f64`s [`blob_values`] stores for each blob.
+const BLOB_VALUES: usize = 5;
+
+fn blob_values(blob: &BlobState) -> [f64; BLOB_VALUES] {
+    [
+        blob.size,
+        blob.position.x,
+        blob.position.y,
+        blob.velocity.x,
+        blob.velocity.y,
+    ]
+}
+
+/// LEB128: seven bits per byte, lowest first, with the top bit set on every
+/// byte but the last.
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn read_varint(&mut self) -> anyhow::Result<u64> {
+        let mut value = 0u64;
+
+        for shift in (0..64).step_by(7) {
+            let (&byte, rest) = self.bytes.split_first().context("Recording is truncated")?;
+            self.bytes = rest;
+
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        bail!("Recording has an invalid varint")
+    }
+
+    /// Read how many of something follow, each at least `min_bytes` long.
+    ///
+    /// Counts too big to fit in what's left of the recording are rejected
+    /// before anything is allocated for them.
+    fn read_count(&mut self, min_bytes: usize) -> anyhow::Result<usize> {
+        let count = self.read_varint()?;
+        if count > (self.bytes.len() / min_bytes) as u64 {
+            bail!("Recording is truncated");
+        }
+
+        Ok(count as usize)
+    }
+
+    fn read_bytes(&mut self, length: usize) -> anyhow::Result<&'a [u8]> {
+        if length > self.bytes.len() {
+            bail!("Recording is truncated");
+        }
+
+        let (bytes, rest) = self.bytes.split_at(length);
+        self.bytes = rest;
+        Ok(bytes)
+    }
+}
@@ -0,0 +1,136 @@
+mod legacy;
+
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
+use std::{
+    convert::TryInto,
+    path::Path,
+};
+
+use crate::{app::Blob, common::config::PhysicsConfig};
+
+/// Bumped whenever the layout of [`Scene`] changes.
+pub const SCENE_VERSION: u32 = 6;
+
+/// Binary scenes start with this, followed by the version as a little endian `u32`.
+const BINARY_MAGIC: &[u8; 8] = b"BLOBBIN\0";
+
+/// Everything needed to recreate a `World` exactly.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Scene {
+    pub version: u32,
+    pub width: usize,
+    pub height: usize,
+    pub seed: u64,
+    pub tick: u64,
+    pub config: PhysicsConfig,
+    pub blobs: Vec<Blob>,
+    /// The id the next blob added will get. Missing from version 2 and older
+    /// scenes, where it is worked out from `blobs`.
+    #[serde(default)]
+    pub next_blob_id: u64,
+    /// How many words the world's random number generator has produced.
+    /// Missing from version 4 and older scenes.
+    #[serde(default)]
+    pub rng_position: u64,
+}
+
+/// How a scene is stored on disk, picked by file extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SceneFormat {
+    /// `.json`, for reading and editing by hand.
+    Json,
+    /// `.scene`, compact and exact.
+    Binary,
+}
+
+impl SceneFormat {
+    pub fn from_path(path: &Path) -> anyhow::Result<Self> {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => Ok(SceneFormat::Json),
+            Some("scene") => Ok(SceneFormat::Binary),
+            _ => bail!(
+                "{}: unknown scene format, expected a .json or .scene file",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl Scene {
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let bytes = self.to_bytes(SceneFormat::from_path(path)?)?;
+
+        std::fs::write(path, bytes).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let format = SceneFormat::from_path(path)?;
+        let bytes = std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+
+        Self::from_bytes(format, &bytes).with_context(|| format!("Failed to load {}", path.display()))
+    }
+
+    pub fn to_bytes(&self, format: SceneFormat) -> anyhow::Result<Vec<u8>> {
+        match format {
+            SceneFormat::Json => {
+                let mut bytes = serde_json::to_vec_pretty(self)?;
+                bytes.push(b'\n');
+                Ok(bytes)
+            }
+            SceneFormat::Binary => {
+                let mut bytes = BINARY_MAGIC.to_vec();
+                bytes.extend_from_slice(&self.version.to_le_bytes());
+                bincode::serialize_into(&mut bytes, self)?;
+                Ok(bytes)
+            }
+        }
+    }
+
+    /// Check the version before parsing the rest, so scenes from a newer
+    /// build get a clear error instead of a confusing parse failure.
+    pub fn from_bytes(format: SceneFormat, bytes: &[u8]) -> anyhow::Result<Self> {
+        match format {
+            SceneFormat::Json => {
+                let value: serde_json::Value = serde_json::from_slice(bytes)?;
+                let version = value
+                    .get("version")
+                    .and_then(serde_json::Value::as_u64)
+                    .context("Scene has no version")?;
+                check_version(version)?;
+
+                Ok(serde_json::from_value(value)?)
+            }
+            SceneFormat::Binary => {
+                let header_length = BINARY_MAGIC.len() + 4;
+                if bytes.len() < header_length || &bytes[..BINARY_MAGIC.len()] != BINARY_MAGIC {
+                    bail!("Not a binary scene");
+                }
+
+                let version = u32::from_le_bytes(bytes[BINARY_MAGIC.len()..header_length].try_into()?);
+                check_version(version as u64)?;
+
+                // Unlike JSON, bincode can't fill in fields added since
+                if version < 5 {
+                    return legacy::from_bytes(version, &bytes[header_length..]);
+                }
+
+                Ok(bincode::deserialize(&bytes[header_length..])?)
+            }
+        }
+    }
+}
+
+fn check_version(version: u64) -> anyhow::Result<()> {
+    if version > SCENE_VERSION as u64 {
+        bail!(
+            "Scene version {} is newer than the newest supported version {}",
+            version,
+            SCENE_VERSION
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,190 @@
+//! Binary scene layouts from before version 5.
+//!
+//! bincode can't skip or fill in fields, so each old layout is spelled out
+//! here and turned into a [`Scene`] that keeps its original `version`, which
+//! lets `World::from_scene` apply the same fixes it does for old JSON scenes.
+//! Some settings were added without a version bump, so versions 1 and 2 try
+//! each layout they were saved with.
+
+use anyhow::{anyhow, bail};
+use bincode::Options;
+use serde::{de::DeserializeOwned, Deserialize};
+
+use super::Scene;
+use crate::{
+    app::{Blob, BlobId},
+    common::{
+        config::{BoundaryConfig, CollisionConfig, ForceConfig, IntegratorKind, PhysicsConfig},
+        math::Vector2f,
+    },
+};
+
+/// Read the payload of a binary scene from before version 5.
+pub(super) fn from_bytes(version: u32, payload: &[u8]) -> anyhow::Result<Scene> {
+    let scene = match version {
+        1 => decode::<WithForces, BlobV1>(payload, false)
+            .or_else(|_| decode::<Settings, BlobV1>(payload, false)),
+        2 => decode::<WithBoundary, BlobV1>(payload, false)
+            .or_else(|_| decode::<WithCollisions, BlobV1>(payload, false))
+            .or_else(|_| decode::<WithIntegrator, BlobV1>(payload, false)),
+        3 => decode::<WithBoundary, BlobV1>(payload, true),
+        4 => decode::<PhysicsConfig, Blob>(payload, true),
+        _ => bail!("Scene version {} has no binary layout", version),
+    };
+
+    scene.map_err(|error| anyhow!("Not a version {} binary scene: {}", version, error))
+}
+
+/// Decode the fields every version shares, then `next_blob_id` if the layout
+/// has one. Trailing bytes are an error, so a wrong guess at the layout fails.
+fn decode<C, B>(payload: &[u8], has_next_blob_id: bool) -> bincode::Result<Scene>
+where
+    C: DeserializeOwned + Into<PhysicsConfig>,
+    B: DeserializeOwned + Into<Blob>,
+{
+    let options = bincode::DefaultOptions::new().with_fixint_encoding();
+    let (scene, next_blob_id) = if has_next_blob_id {
+        options.deserialize::<(OldScene<C, B>, u64)>(payload)?
+    } else {
+        (options.deserialize::<OldScene<C, B>>(payload)?, 0)
+    };
+
+    Ok(Scene {
+        version: scene.version,
+        width: scene.width,
+        height: scene.height,
+        seed: scene.seed,
+        tick: scene.tick,
+        config: scene.config.into(),
+        blobs: scene.blobs.into_iter().map(Into::into).collect(),
+        next_blob_id,
+        rng_position: 0,
+    })
+}
+
+#[derive(Deserialize)]
+struct OldScene<C, B> {
+    version: u32,
+    width: usize,
+    height: usize,
+    seed: u64,
+    tick: u64,
+    config: C,
+    blobs: Vec<B>,
+}
+
+/// The physics settings every version has, in their original order.
+#[derive(Deserialize)]
+struct Settings {
+    blob_size: f64,
+    repel_force: f64,
+    repel_distance: f64,
+    friction_force: f64,
+    max_acceleration: f64,
+    min_acceleration: f64,
+}
+
+impl From<Settings> for PhysicsConfig {
+    fn from(settings: Settings) -> Self {
+        PhysicsConfig {
+            blob_size: settings.blob_size,
+            repel_force: settings.repel_force,
+            repel_distance: settings.repel_distance,
+            friction_force: settings.friction_force,
+            max_acceleration: settings.max_acceleration,
+            min_acceleration: settings.min_acceleration,
+            ..PhysicsConfig::default()
+        }
+    }
+}
+
+/// Version 1, once forces were added.
+#[derive(Deserialize)]
+struct WithForces {
+    settings: Settings,
+    forces: Vec<ForceConfig>,
+}
+
+impl From<WithForces> for PhysicsConfig {
+    fn from(config: WithForces) -> Self {
+        PhysicsConfig {
+            forces: config.forces,
+            ..config.settings.into()
+        }
+    }
+}
+
+/// Version 2 as first saved.
+#[derive(Deserialize)]
+struct WithIntegrator {
+    settings: Settings,
+    integrator: IntegratorKind,
+    forces: Vec<ForceConfig>,
+}
+
+impl From<WithIntegrator> for PhysicsConfig {
+    fn from(config: WithIntegrator) -> Self {
+        PhysicsConfig {
+            integrator: config.integrator,
+            forces: config.forces,
+            ..config.settings.into()
+        }
+    }
+}
+
+/// Version 2, once collisions were added.
+#[derive(Deserialize)]
+struct WithCollisions {
+    config: WithIntegrator,
+    collisions: CollisionConfig,
+}
+
+impl From<WithCollisions> for PhysicsConfig {
+    fn from(config: WithCollisions) -> Self {
+        PhysicsConfig {
+            collisions: config.collisions,
+            ..config.config.into()
+        }
+    }
+}
+
+/// Version 2 once boundaries were added, and version 3.
+#[derive(Deserialize)]
+struct WithBoundary {
+    config: WithCollisions,
+    boundary: BoundaryConfig,
+}
+
+impl From<WithBoundary> for PhysicsConfig {
+    fn from(config: WithBoundary) -> Self {
+        PhysicsConfig {
+            boundary: config.boundary,
+            ..config.config.into()
+        }
+    }
+}
+
+/// A blob from before species, with its mass left to be worked out on load.
+#[derive(Deserialize)]
+struct BlobV1 {
+    id: u64,
+    size: f64,
+    position: Vector2f,
+    previous_position: Vector2f,
+    velocity: Vector2f,
+    acceleration: Vector2f,
+}
+
+impl From<BlobV1> for Blob {
+    fn from(blob: BlobV1) -> Self {
+        Blob {
+            id: BlobId::new(blob.id),
+            size: blob.size,
+            position: blob.position,
+            previous_position: blob.previous_position,
+            velocity: blob.velocity,
+            acceleration: blob.acceleration,
+            ..Blob::default()
+        }
+    }
+}
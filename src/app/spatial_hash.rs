@@ -0,0 +1,160 @@
+use crate::{
+    app::{boundary, Blob},
+    common::math::{Grid, Vector2f},
+};
+
+const MAX_CELLS: usize = 1 << 20;
+
+/// Uniform grid broad phase over the blobs in a `World`.
+///
+/// Blobs are bucketed by position into square cells of `cell_size`. As long as
+/// `cell_size` is at least the largest interaction distance, every neighbor of
+/// a blob lives in the 3x3 block of cells around it.
+pub struct SpatialHash {
+    cell_size: f64,
+    grid: Grid<Vec<usize>>,
+    /// Reused by `for_each_neighbor_wrapped` to find each neighbor once.
+    wrapped: Vec<usize>,
+}
+
+impl SpatialHash {
+    pub fn new(width: f64, height: f64, cell_size: f64) -> Self {
+        let cell_size = Self::sanitize_cell_size(width, height, cell_size);
+        let (columns, rows) = Self::dimensions(width, height, cell_size);
+
+        Self {
+            cell_size,
+            grid: Grid::new(columns, rows),
+            wrapped: Vec::new(),
+        }
+    }
+
+    pub fn cell_size(&self) -> f64 {
+        self.cell_size
+    }
+
+    /// Re-bucket every blob, resizing the grid if the world or cell size changed.
+    ///
+    /// Buckets store indices into `blobs`, not blob ids.
+    pub fn rebuild(&mut self, width: f64, height: f64, cell_size: f64, blobs: &[Blob]) {
+        let cell_size = Self::sanitize_cell_size(width, height, cell_size);
+        let (columns, rows) = Self::dimensions(width, height, cell_size);
+
+        if cell_size != self.cell_size || columns != self.grid.width || rows != self.grid.height {
+            self.cell_size = cell_size;
+            self.grid = Grid::new(columns, rows);
+        } else {
+            // Keep the bucket allocations around between ticks
+            for cell in self.grid.cells_mut() {
+                cell.clear();
+            }
+        }
+
+        for (index, blob) in blobs.iter().enumerate() {
+            let cell = self.cell_of(&blob.position);
+            self.grid[cell].push(index);
+        }
+    }
+
+    /// Call `f` with the index of every blob in the cells surrounding `position`.
+    ///
+    /// This is a superset of the blobs within `cell_size` of `position`, and
+    /// includes the blob at `position` itself if it was bucketed.
+    pub fn for_each_neighbor(&self, position: &Vector2f, mut f: impl FnMut(usize)) {
+        let (x, y) = self.cell_of(position);
+
+        let min_x = x.saturating_sub(1);
+        let max_x = (x + 1).min(self.grid.width - 1);
+        let min_y = y.saturating_sub(1);
+        let max_y = (y + 1).min(self.grid.height - 1);
+
+        for cell_y in min_y..=max_y {
+            for cell_x in min_x..=max_x {
+                for &index in &self.grid[(cell_x, cell_y)] {
+                    f(index);
+                }
+            }
+        }
+    }
+
+    /// Like [`SpatialHash::for_each_neighbor`], in a world that wraps around
+    /// at `width` and `height`.
+    ///
+    /// `f` is also given the offset that moves each neighbor to its image
+    /// nearest `position`. Every neighbor is passed once, in index order, even
+    /// in worlds small enough for it to be close across more than one edge.
+    pub fn for_each_neighbor_wrapped(
+        &mut self,
+        position: &Vector2f,
+        blobs: &[Blob],
+        width: f64,
+        height: f64,
+        mut f: impl FnMut(usize, &Vector2f),
+    ) {
+        let mut found = std::mem::take(&mut self.wrapped);
+        found.clear();
+
+        for shift_x in wrap_shifts(position.x, width, self.cell_size).iter().flatten() {
+            for shift_y in wrap_shifts(position.y, height, self.cell_size).iter().flatten() {
+                // Searching around the query shifted across an edge finds the
+                // neighbors that are close through that edge. Shifted queries
+                // land in the edge cells, see `cell_of`.
+                let query = Vector2f::new(position.x + shift_x, position.y + shift_y);
+                self.for_each_neighbor(&query, |index| found.push(index));
+            }
+        }
+
+        found.sort_unstable();
+        found.dedup();
+        for &index in &found {
+            let other = &blobs[index].position;
+            let offset = &boundary::nearest_image(position, other, width, height) - other;
+            f(index, &offset);
+        }
+
+        self.wrapped = found;
+    }
+
+    /// Positions outside of the world are clamped into the edge cells.
+    #[inline]
+    fn cell_of(&self, position: &Vector2f) -> (usize, usize) {
+        let x = (position.x / self.cell_size).max(0.0) as usize;
+        let y = (position.y / self.cell_size).max(0.0) as usize;
+
+        (x.min(self.grid.width - 1), y.min(self.grid.height - 1))
+    }
+
+    #[inline]
+    fn dimensions(width: f64, height: f64, cell_size: f64) -> (usize, usize) {
+        // One extra cell so that positions exactly on the far edge have a home
+        let columns = (width.max(0.0) / cell_size) as usize + 1;
+        let rows = (height.max(0.0) / cell_size) as usize + 1;
+
+        (columns, rows)
+    }
+
+    /// Larger cells are always correct, just slower, so tiny or invalid cell
+    /// sizes are grown until the grid fits in `MAX_CELLS`.
+    #[inline]
+    fn sanitize_cell_size(width: f64, height: f64, cell_size: f64) -> f64 {
+        let area = width.max(1.0) * height.max(1.0);
+        let min_cell_size = (area / MAX_CELLS as f64).sqrt();
+
+        if cell_size.is_finite() && cell_size > min_cell_size {
+            cell_size
+        } else {
+            min_cell_size.max(1.0)
+        }
+    }
+}
+
+/// How far to shift a query along one axis to look across the edges it is
+/// within `reach` of.
+#[inline]
+fn wrap_shifts(value: f64, extent: f64, reach: f64) -> [Option<f64>; 3] {
+    [
+        Some(0.0),
+        if value <= reach { Some(extent) } else { None },
+        if value >= extent - reach { Some(-extent) } else { None },
+    ]
+}
@@ -1,41 +1,72 @@
-use wgpu::Surface;
+use futures::executor::block_on;
 use winit::dpi::LogicalSize;
 use winit::event_loop::EventLoopWindowTarget;
 use winit::window::{Window, WindowBuilder};
 use winit_input_helper::WinitInputHelper;
 
+use crate::common::config::{Config, WindowConfig};
+use crate::graphics;
+
 pub struct AppState {
     pub window: Window,
     pub input: WinitInputHelper,
-    pub pixels: Pixels,
+    pub graphics: graphics::State,
+    window_config: WindowConfig,
 }
 
 impl AppState {
-    pub fn new(width: u32, height: u32, event_loop: &EventLoopWindowTarget<()>) -> anyhow::Result<Self> {
+    pub fn new(config: &Config, event_loop: &EventLoopWindowTarget<()>) -> anyhow::Result<Self> {
         let input = WinitInputHelper::new();
         let window = {
-            let size = LogicalSize::new(width as f64, height as f64);
+            let size = LogicalSize::new(config.window.width as f64, config.window.height as f64);
             WindowBuilder::new()
-                .with_title("Blobbin")
+                .with_title(&config.window.title)
                 .with_inner_size(size)
                 .with_min_inner_size(size)
-                .build(&event_loop)
-                .unwrap()
+                .with_resizable(config.window.resizable)
+                .build(&event_loop)?
         };
     
-        let pixels = {
-            let window_size = window.inner_size();
-            let surface = Surface::create(&window);
-            let surface_texture = SurfaceTexture::new(window_size.width, window_size.height, surface);
-            Pixels::new(width, height, surface_texture)?
-        };
+        let graphics = block_on(graphics::State::new(&window, config.graphics.clone(), &config.camera))?;
 
         let state = Self {
             window,
             input,
-            pixels,
+            graphics,
+            window_config: config.window.clone(),
         };
 
         Ok(state)
     }
+
+    /// Apply the window, graphics and camera sections of a reloaded config.
+    pub fn apply_config(&mut self, config: &Config) {
+        let window = &config.window;
+        if window.title != self.window_config.title {
+            self.window.set_title(&window.title);
+        }
+        if window.width != self.window_config.width || window.height != self.window_config.height {
+            let size = LogicalSize::new(window.width as f64, window.height as f64);
+            self.window.set_min_inner_size(Some(size));
+            self.window.set_inner_size(size);
+        }
+        if window.resizable != self.window_config.resizable {
+            self.window.set_resizable(window.resizable);
+        }
+        self.window_config = window.clone();
+
+        self.graphics.apply_config(&config.graphics, &config.camera);
+    }
+
+    /// Show why the config failed to reload in the title bar, or clear it
+    /// again once it loads.
+    pub fn show_config_error(&mut self, error: Option<&anyhow::Error>) {
+        match error {
+            Some(error) => {
+                let title = format!("{} - invalid config: {:#}", self.window_config.title, error);
+                self.window.set_title(&title);
+            }
+            None => self.window.set_title(&self.window_config.title),
+        }
+    }
 }
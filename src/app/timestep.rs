@@ -0,0 +1,46 @@
+/// The most simulation steps a single frame is allowed to run before the
+/// remaining time is dropped, so a long stall can't snowball into ever longer
+/// frames.
+const MAX_STEPS_PER_FRAME: usize = 8;
+
+/// Turns variable wall-clock frame times into a whole number of fixed-size
+/// simulation steps.
+#[derive(Debug, Clone)]
+pub struct FixedTimestep {
+    step: f64,
+    accumulator: f64,
+}
+
+impl FixedTimestep {
+    pub fn new(step: f64) -> Self {
+        Self {
+            step,
+            accumulator: 0.0,
+        }
+    }
+
+    pub fn step(&self) -> f64 {
+        self.step
+    }
+
+    /// Add `delta_time` seconds of elapsed time and return how many fixed steps
+    /// should be simulated to catch up.
+    pub fn advance(&mut self, delta_time: f64) -> usize {
+        self.accumulator += delta_time.max(0.0);
+
+        let steps = (self.accumulator / self.step) as usize;
+        if steps > MAX_STEPS_PER_FRAME {
+            self.accumulator = 0.0;
+            return MAX_STEPS_PER_FRAME;
+        }
+
+        self.accumulator -= steps as f64 * self.step;
+        steps
+    }
+
+    /// How far between the last two simulation steps the current frame falls,
+    /// from `0.0` (the previous step) to `1.0` (the latest step).
+    pub fn alpha(&self) -> f64 {
+        self.accumulator / self.step
+    }
+}
@@ -0,0 +1,225 @@
+use crate::{
+    app::{BlobId, World, WorldInput},
+    common::math::Vector2f,
+};
+
+/// How far from the cursor `Tool::Push` reaches.
+pub const PUSH_RADIUS: f64 = 50.0;
+/// How quickly `Tool::Push` speeds up blobs right next to the cursor, in units
+/// per second per second.
+pub const PUSH_STRENGTH: f64 = 1000.0;
+/// How much of the cursor's newest speed goes into the speed a dragged blob is
+/// flung at, the rest coming from its older speed.
+const FLING_SMOOTHING: f64 = 0.5;
+
+/// What holding a mouse button down over the world does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    /// Pick up the blob under the cursor and fling it on release, or add a
+    /// blob if there is none.
+    Grab,
+    /// Add blobs all along the cursor's path.
+    Paint,
+    /// Push blobs away from the cursor, or pull them in with the secondary
+    /// button.
+    Push,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseButton {
+    Primary,
+    Secondary,
+}
+
+#[derive(Debug, Clone)]
+enum Gesture {
+    Idle,
+    Dragging {
+        id: BlobId,
+        /// From the cursor to the blob's top left corner.
+        grab_offset: Vector2f,
+        last_cursor: Vector2f,
+        velocity: Vector2f,
+    },
+    Painting {
+        last_spawn: Vector2f,
+    },
+    Pushing {
+        strength: f64,
+    },
+}
+
+/// Turns the mouse, in world coordinates, into `WorldInput`s.
+///
+/// Everything goes through inputs so that sessions using the tools can still
+/// be recorded and replayed.
+#[derive(Debug, Clone)]
+pub struct Tools {
+    pub tool: Tool,
+    /// The species `Grab` and `Paint` add blobs of.
+    pub species: usize,
+    pub push_radius: f64,
+    pub push_strength: f64,
+    cursor: Option<Vector2f>,
+    button: Option<MouseButton>,
+    gesture: Gesture,
+}
+
+impl Default for Tools {
+    fn default() -> Self {
+        Self {
+            tool: Tool::Grab,
+            species: 0,
+            push_radius: PUSH_RADIUS,
+            push_strength: PUSH_STRENGTH,
+            cursor: None,
+            button: None,
+            gesture: Gesture::Idle,
+        }
+    }
+}
+
+impl Tools {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Switch tools, letting go of whatever the current one is doing.
+    pub fn select(&mut self, tool: Tool) -> Vec<WorldInput> {
+        let inputs = match self.button {
+            Some(button) => self.release(button),
+            None => Vec::new(),
+        };
+        self.tool = tool;
+
+        inputs
+    }
+
+    /// Where the cursor is in the world, or `None` if it isn't over it.
+    pub fn move_cursor(&mut self, cursor: Option<Vector2f>) {
+        self.cursor = cursor;
+    }
+
+    pub fn press(&mut self, button: MouseButton, world: &World) -> Vec<WorldInput> {
+        let cursor = match (&self.cursor, self.button) {
+            (Some(cursor), None) => cursor.clone(),
+            _ => return Vec::new(),
+        };
+        self.button = Some(button);
+
+        match (self.tool, button) {
+            (Tool::Grab, MouseButton::Primary) => match world.blob_at(&cursor) {
+                Some(id) => {
+                    let blob = world.blob(id).expect("blob_at returns blobs in the world");
+                    self.gesture = Gesture::Dragging {
+                        id,
+                        grab_offset: &blob.position - &cursor,
+                        last_cursor: cursor,
+                        velocity: Vector2f::ZERO,
+                    };
+                    Vec::new()
+                }
+                None => vec![self.add_blob(&cursor, world)],
+            },
+            (Tool::Paint, MouseButton::Primary) => {
+                let input = self.add_blob(&cursor, world);
+                self.gesture = Gesture::Painting { last_spawn: cursor };
+                vec![input]
+            }
+            (Tool::Push, _) => {
+                let strength = match button {
+                    MouseButton::Primary => self.push_strength,
+                    MouseButton::Secondary => -self.push_strength,
+                };
+                self.gesture = Gesture::Pushing { strength };
+                Vec::new()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Let go of `button`. A dragged blob is flung at the speed the cursor was
+    /// moving.
+    pub fn release(&mut self, button: MouseButton) -> Vec<WorldInput> {
+        if self.button != Some(button) {
+            return Vec::new();
+        }
+        self.button = None;
+
+        match std::mem::replace(&mut self.gesture, Gesture::Idle) {
+            Gesture::Dragging { id, velocity, .. } => vec![WorldInput::SetBlobVelocity {
+                id,
+                x: velocity.x,
+                y: velocity.y,
+            }],
+            _ => Vec::new(),
+        }
+    }
+
+    /// The inputs to apply before the next `delta_time` long tick of `world`.
+    pub fn tick(&mut self, world: &World, delta_time: f64) -> Vec<WorldInput> {
+        let cursor = match &self.cursor {
+            Some(cursor) => cursor.clone(),
+            None => return Vec::new(),
+        };
+        let size = self.species_size(world);
+        let spawn = self.add_blob(&cursor, world);
+
+        match &mut self.gesture {
+            Gesture::Idle => Vec::new(),
+            Gesture::Dragging {
+                id,
+                grab_offset,
+                last_cursor,
+                velocity,
+            } => {
+                if delta_time > 0.0 {
+                    let current = (&cursor - &*last_cursor) / delta_time;
+                    *velocity = &(FLING_SMOOTHING * current) + (1.0 - FLING_SMOOTHING) * &*velocity;
+                }
+                *last_cursor = cursor.clone();
+
+                let position = &cursor + &*grab_offset;
+                vec![WorldInput::MoveBlob {
+                    id: *id,
+                    x: position.x,
+                    y: position.y,
+                }]
+            }
+            Gesture::Painting { last_spawn } => {
+                // Leave a blob's width between each blob
+                if last_spawn.vector_to(&cursor).magnitude() < size {
+                    return Vec::new();
+                }
+                *last_spawn = cursor;
+
+                vec![spawn]
+            }
+            Gesture::Pushing { strength } => vec![WorldInput::PushBlobs {
+                x: cursor.x,
+                y: cursor.y,
+                radius: self.push_radius,
+                strength: *strength * delta_time,
+            }],
+        }
+    }
+
+    /// Add a blob centered on `cursor`.
+    fn add_blob(&self, cursor: &Vector2f, world: &World) -> WorldInput {
+        let radius = self.species_size(world) / 2.0;
+
+        WorldInput::AddBlob {
+            x: cursor.x - radius,
+            y: cursor.y - radius,
+            species: self.species,
+        }
+    }
+
+    fn species_size(&self, world: &World) -> f64 {
+        world
+            .config
+            .species
+            .get(self.species)
+            .map_or(world.config.blob_size, |species| species.size)
+    }
+}
@@ -1,40 +1,213 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+
 use crate::{
-    common::math::{self, Vector2f}, 
-    app::Blob,
-    common::config::Config,
+    common::math::Vector2f,
+    app::{
+        blob::mass_for_size, boundary, collision,
+        integrators::build_integrator, Blob, BlobEvent, BlobId, Force, ForceSet, InputLog, Integrator, Scene,
+        SpatialHash, WorldInput, SCENE_VERSION,
+    },
+    common::config::{BoundaryMode, PhysicsConfig},
+    common::events::{EventChannel, Subscriber},
 };
 
-const WHITE: [u8; 4] = [255, 255, 255, 255];
-
 pub struct World {
     pub width: usize,
     pub height: usize,
-    pub config: Config,
-    pub blobs: Vec<Blob>
+    pub config: PhysicsConfig,
+    /// Always sorted by id, which is the order blobs were added in.
+    pub blobs: Vec<Blob>,
+    next_blob_id: u64,
+    seed: u64,
+    tick: u64,
+    rng: ChaCha20Rng,
+    spatial_hash: SpatialHash,
+    force_set: ForceSet,
+    integrator: Box<dyn Integrator>,
+    net_forces: Vec<Vector2f>,
+    blob_events: EventChannel<BlobEvent>,
 }
 
 impl World {
-    pub fn refresh_config(&mut self) -> anyhow::Result<()> {
-        let new_config = Config::load_default_config_file()?;
-        self.config.print_config_diff(&new_config);
+    /// Create a `World` with a random seed.
+    pub fn new(width: usize, height: usize, config: PhysicsConfig) -> Self {
+        Self::with_seed(width, height, config, rand::random())
+    }
+
+    /// Create a `World` whose random blob placement is fully determined by `seed`.
+    pub fn with_seed(width: usize, height: usize, config: PhysicsConfig, seed: u64) -> Self {
+        let spatial_hash = SpatialHash::new(width as f64, height as f64, config.repel_distance);
+        let integrator = build_integrator(config.integrator);
+
+        Self {
+            width,
+            height,
+            config,
+            blobs: Vec::new(),
+            next_blob_id: 0,
+            seed,
+            tick: 0,
+            rng: seeded_rng(seed),
+            spatial_hash,
+            force_set: ForceSet::new(),
+            integrator,
+            net_forces: Vec::new(),
+            blob_events: EventChannel::new(),
+        }
+    }
+
+    /// Recreate a saved `World`.
+    ///
+    /// Blob positions, velocities and the random number generator are restored
+    /// exactly, so the world carries on as if it had never been saved. Version 4
+    /// and older scenes don't store the generator's position, and reseed it from
+    /// the scene's seed and tick instead.
+    pub fn from_scene(scene: Scene) -> Self {
+        let mut world = Self::with_seed(scene.width, scene.height, scene.config, scene.seed);
+        world.tick = scene.tick;
+        if scene.version < 5 {
+            world.rng = seeded_rng(scene.seed ^ scene.tick);
+        } else {
+            world.rng.set_word_pos(scene.rng_position as u128);
+        }
+        world.blobs = scene.blobs;
+        world.blobs.sort_by_key(|blob| blob.id);
+
+        // Masses from version 5 and older scenes were areas rather than
+        // relative to `blob_size`
+        for index in 0..world.blobs.len() {
+            let blob = &world.blobs[index];
+            if scene.version < 6 || blob.mass <= 0.0 {
+                let mass = world.species_mass(blob.species, blob.size);
+                world.blobs[index].mass = mass;
+            }
+        }
 
-        self.config = new_config;
-        Ok(())
+        let after_last = world.blobs.last().map_or(0, |blob| blob.id.get() + 1);
+        world.next_blob_id = scene.next_blob_id.max(after_last);
+        world
     }
 
-    pub fn add_blob(&mut self, x: f64, y: f64) {
-        let id = self.blobs.len();
-        self.blobs.push(Blob::new(id, self.config.blob_size, (x, y)));
+    pub fn to_scene(&self) -> Scene {
+        Scene {
+            version: SCENE_VERSION,
+            width: self.width,
+            height: self.height,
+            seed: self.seed,
+            tick: self.tick,
+            config: self.config.clone(),
+            blobs: self.blobs.clone(),
+            next_blob_id: self.next_blob_id,
+            rng_position: self.rng.get_word_pos() as u64,
+        }
+    }
+
+    /// Save as JSON or binary, depending on the extension. See [`SceneFormat`](crate::app::SceneFormat).
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+        self.to_scene().save(path)
     }
 
-    pub fn add_random_blob(&mut self) {
-        use rand::Rng;
-        let mut rng = rand::thread_rng();
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        Ok(Self::from_scene(Scene::load(path)?))
+    }
 
-        let x = rng.gen_range(0, self.width) as f64;
-        let y =  rng.gen_range(0, self.height) as f64;
-        
-        self.add_blob(x, y);
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The number of times `update` has been called.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Add a force on top of the ones in the config.
+    pub fn add_force(&mut self, force: Box<dyn Force>) {
+        self.force_set.add(force);
+    }
+
+    /// Remove forces added with [`World::add_force`] by name, returning how
+    /// many were removed.
+    pub fn remove_force(&mut self, name: &str) -> usize {
+        self.force_set.remove(name)
+    }
+
+    /// Be told about every blob added or removed from now on.
+    pub fn subscribe_to_blobs(&mut self) -> Subscriber<BlobEvent> {
+        self.blob_events.subscribe()
+    }
+
+    /// Add a blob of the first species, or a plain `blob_size` blob if there
+    /// are no species.
+    pub fn add_blob(&mut self, x: f64, y: f64) -> BlobId {
+        self.add_blob_of_species(x, y, 0)
+    }
+
+    /// Add a blob with the size and mass of `species`.
+    pub fn add_blob_of_species(&mut self, x: f64, y: f64, species: usize) -> BlobId {
+        let id = BlobId::new(self.next_blob_id);
+        self.next_blob_id += 1;
+
+        let size = self
+            .config
+            .species
+            .get(species)
+            .map_or(self.config.blob_size, |species| species.size);
+        let mut blob = Blob::new(id, size, (x, y));
+        blob.species = species;
+        blob.mass = self.species_mass(species, size);
+
+        self.blobs.push(blob);
+        self.blob_events.publish(BlobEvent::Spawned(id));
+
+        id
+    }
+
+    /// The mass of a blob of `species` and `size`: the species' mass if it
+    /// sets one, otherwise in proportion to the blob's area.
+    fn species_mass(&self, species: usize, size: f64) -> f64 {
+        self.config
+            .species
+            .get(species)
+            .and_then(|species| species.mass)
+            .unwrap_or_else(|| mass_for_size(size, self.config.blob_size))
+    }
+
+    /// Add a blob of a random species at a random position.
+    pub fn add_random_blob(&mut self) -> BlobId {
+        let x = self.rng.gen_range(0, self.width) as f64;
+        let y = self.rng.gen_range(0, self.height) as f64;
+        let species = match self.config.species.len() {
+            0 | 1 => 0,
+            count => self.rng.gen_range(0, count),
+        };
+
+        self.add_blob_of_species(x, y, species)
+    }
+
+    /// Returns `None` if there is no blob with that id, for example because it
+    /// was already removed.
+    pub fn remove_blob(&mut self, id: BlobId) -> Option<Blob> {
+        let index = self.blob_index(id)?;
+        let blob = self.blobs.remove(index);
+        self.blob_events.publish(BlobEvent::Despawned(id));
+
+        Some(blob)
+    }
+
+    pub fn blob(&self, id: BlobId) -> Option<&Blob> {
+        self.blob_index(id).map(|index| &self.blobs[index])
+    }
+
+    pub fn blob_mut(&mut self, id: BlobId) -> Option<&mut Blob> {
+        let index = self.blob_index(id)?;
+        Some(&mut self.blobs[index])
+    }
+
+    fn blob_index(&self, id: BlobId) -> Option<usize> {
+        self.blobs.binary_search_by_key(&id, |blob| blob.id).ok()
     }
 
     pub fn add_random_blobs(&mut self, count: usize) {
@@ -43,73 +216,277 @@ impl World {
         }
     }
 
-    /// Update the `World` internal state; bounce the box around the screen.
-    pub fn update(&mut self, delta_time: f64) {
-        for index in 0..self.blobs.len() {
-            let blob = &self.blobs[index];
-            let mut blob_forces: Vec<Vector2f> = Vec::new();
-            
-            for other in &self.blobs {
-                if blob.id != other.id {
-                    let force_vector = blob.position.vector_to(&other.position);
-                    if force_vector.magnitude() <= self.config.repel_distance {
-                        blob_forces.push(self.config.repel_force * force_vector.normalized());
-                    }    
+    pub fn apply_input(&mut self, input: &WorldInput) {
+        match *input {
+            WorldInput::AddBlob { x, y, species } => {
+                self.add_blob_of_species(x, y, species);
+            }
+            WorldInput::AddRandomBlobs { count } => self.add_random_blobs(count),
+            WorldInput::RemoveBlob { id } => {
+                self.remove_blob(id);
+            }
+            WorldInput::MoveBlob { id, x, y } => {
+                if let Some(blob) = self.blob_mut(id) {
+                    blob.position = Vector2f::new(x, y);
+                    blob.velocity = Vector2f::ZERO;
                 }
             }
+            WorldInput::SetBlobVelocity { id, x, y } => {
+                if let Some(blob) = self.blob_mut(id) {
+                    blob.velocity = Vector2f::new(x, y);
+                }
+            }
+            WorldInput::PushBlobs { x, y, radius, strength } => {
+                self.push_blobs(&Vector2f::new(x, y), radius, strength);
+            }
+            WorldInput::SetConfig { ref config } => self.config = config.clone(),
+            WorldInput::Resize { width, height } => {
+                self.width = width;
+                self.height = height;
+            }
+        }
+    }
 
-            let blob = &mut self.blobs[index];
+    /// The blob under `point`, picked by the shape blobs collide as. Where
+    /// blobs overlap the most recently added one wins.
+    pub fn blob_at(&self, point: &Vector2f) -> Option<BlobId> {
+        let shape = self.config.collisions.shape;
 
-            for force in blob_forces {
-                blob.acceleration += force * delta_time;
-            }
+        self.blobs
+            .iter()
+            .rev()
+            .find(|blob| blob.contains_point(&self.nearest_image(&blob.center(), point), shape))
+            .map(|blob| blob.id)
+    }
 
-            if blob.position.x <= 0.0 || blob.position.x + self.config.blob_size > self.width as f64 {
-                blob.velocity.x *= -1.0;
-            }
-            
-            if blob.position.y <= 0.0 || blob.position.y + self.config.blob_size > self.height as f64 {
-                blob.velocity.y *= -1.0;
+    /// Change the velocity of every blob whose center is within `radius` of
+    /// `center` by up to `strength`, away from `center`, fading out to nothing
+    /// at `radius`. A negative `strength` pulls blobs in instead.
+    pub fn push_blobs(&mut self, center: &Vector2f, radius: f64, strength: f64) {
+        if radius <= 0.0 {
+            return;
+        }
+
+        for index in 0..self.blobs.len() {
+            let blob_center = self.blobs[index].center();
+            let offset = &blob_center - &self.nearest_image(&blob_center, center);
+            let distance = offset.magnitude();
+
+            // A blob right on the cursor has no direction to be pushed in
+            if distance > 0.0 && distance < radius {
+                self.blobs[index].velocity += strength * (1.0 - distance / radius) * offset.normalized();
             }
+        }
+    }
 
-            let drag = blob.velocity.clone() * self.config.friction_force * delta_time;
-            blob.acceleration -= drag;
+    /// Where `point` is closest to `position`, which is only ever somewhere
+    /// else if the world wraps.
+    fn nearest_image(&self, position: &Vector2f, point: &Vector2f) -> Vector2f {
+        match self.config.boundary.mode {
+            BoundaryMode::Wrap => boundary::nearest_image(position, point, self.width as f64, self.height as f64),
+            _ => point.clone(),
+        }
+    }
 
-            blob.velocity.x += blob.acceleration.x * delta_time;
-            blob.velocity.y += blob.acceleration.y * delta_time;
+    /// Apply any logged inputs for the current tick, then advance one tick.
+    pub fn replay_tick(&mut self, log: &InputLog, delta_time: f64) {
+        for input in log.inputs_at(self.tick) {
+            self.apply_input(input);
+        }
+        self.update(delta_time);
+    }
 
-            blob.position.x += blob.velocity.x * delta_time;
-            blob.position.y += blob.velocity.y * delta_time;
+    /// A hash of every blob's id, position, velocity and acceleration.
+    ///
+    /// Two worlds with the same seed, config and inputs hash identically after
+    /// the same number of fixed-size ticks.
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
 
-            blob.position.x = math::clamp(blob.position.x, 0.0, self.width as f64);
-            blob.position.y = math::clamp(blob.position.y, 0.0, self.height as f64);
+        self.tick.hash(&mut hasher);
+        for blob in &self.blobs {
+            blob.id.hash(&mut hasher);
+            for value in &[
+                blob.position.x,
+                blob.position.y,
+                blob.velocity.x,
+                blob.velocity.y,
+                blob.acceleration.x,
+                blob.acceleration.y,
+            ] {
+                value.to_bits().hash(&mut hasher);
+            }
         }
+
+        hasher.finish()
     }
 
-    /// Draw the `World` state to the frame buffer.
+    /// Update the `World` internal state, keeping blobs in bounds as the
+    /// boundary config says.
     ///
-    /// Assumes the default texture format: [`wgpu::TextureFormat::Rgba8UnormSrgb`]
-    pub fn draw(&self, frame: &mut [u8]) {
-        for item in frame.iter_mut() {
-            *item = 0;
+    /// For reproducible results `delta_time` should be a fixed step, see
+    /// [`FixedTimestep`](crate::app::FixedTimestep).
+    pub fn update(&mut self, delta_time: f64) {
+        self.tick += 1;
+        self.force_set.update_config(&self.config);
+        if self.integrator.name() != self.config.integrator.name() {
+            self.integrator = build_integrator(self.config.integrator);
         }
 
-        for blob in &self.blobs {
-            let start = self.position_to_index(&blob.position);
-            let end = start + 4;
+        for blob in &mut self.blobs {
+            blob.previous_position = blob.position.clone();
+        }
 
-            if end <= frame.len() {
-                let pixels = &mut frame[start..end];
-                pixels.copy_from_slice(&WHITE);    
+        let (width, height) = (self.width as f64, self.height as f64);
+        let wrap = self.config.boundary.mode == BoundaryMode::Wrap;
+
+        let force_set = &self.force_set;
+        let spatial_hash = &mut self.spatial_hash;
+        let config = &self.config;
+        self.integrator.step(&mut self.blobs, delta_time, &mut |blobs, accelerations| {
+            compute_forces(force_set, spatial_hash, width, height, wrap, blobs, accelerations);
+
+            for (acceleration, blob) in accelerations.iter_mut().zip(blobs) {
+                *acceleration /= blob.mass;
+                *acceleration = acceleration.clamp_magnitude(config.min_acceleration, config.max_acceleration);
             }
+        });
+
+        if self.config.collisions.enabled {
+            collision::resolve_collisions(
+                &mut self.blobs,
+                &mut self.spatial_hash,
+                width,
+                height,
+                wrap,
+                &self.config.collisions,
+            );
+        }
+
+        for id in boundary::apply(&mut self.blobs, &self.config.boundary, width, height) {
+            self.blob_events.publish(BlobEvent::Despawned(id));
         }
     }
 
-    #[inline]
-    fn position_to_index(&self, position: &Vector2f) -> usize {
-        let index = position.y as usize * self.height + position.x as usize;
+    /// The net force on each blob, in the same order as `blobs`.
+    ///
+    /// Neighbors are found through the spatial hash, so only blobs in adjacent
+    /// cells are ever compared.
+    pub fn forces(&mut self) -> &[Vector2f] {
+        self.force_set.update_config(&self.config);
+        compute_forces(
+            &self.force_set,
+            &mut self.spatial_hash,
+            self.width as f64,
+            self.height as f64,
+            self.config.boundary.mode == BoundaryMode::Wrap,
+            &self.blobs,
+            &mut self.net_forces,
+        );
+
+        &self.net_forces
+    }
+
+    /// The same forces as [`World::forces`], found by comparing every pair of blobs.
+    pub fn brute_force_forces(&mut self) -> Vec<Vector2f> {
+        self.force_set.update_config(&self.config);
+
+        let (width, height) = (self.width as f64, self.height as f64);
+        let wrap = self.config.boundary.mode == BoundaryMode::Wrap;
+
+        self.blobs
+            .iter()
+            .enumerate()
+            .map(|(index, blob)| {
+                let others = self
+                    .blobs
+                    .iter()
+                    .enumerate()
+                    .filter(|&(other, _)| other != index)
+                    .map(|(_, other)| other);
+
+                if wrap {
+                    let images: Vec<Blob> = others
+                        .map(|other| Blob {
+                            position: boundary::nearest_image(&blob.position, &other.position, width, height),
+                            ..other.clone()
+                        })
+                        .collect();
+                    let neighbors: Vec<&Blob> = images.iter().collect();
+                    self.force_set.force_on(blob, &neighbors)
+                } else {
+                    let neighbors: Vec<&Blob> = others.collect();
+                    self.force_set.force_on(blob, &neighbors)
+                }
+            })
+            .collect()
+    }
+}
+
+fn seeded_rng(seed: u64) -> ChaCha20Rng {
+    let mut rng = ChaCha20Rng::seed_from_u64(seed);
+    // `get_word_pos` only works once the first block has been generated
+    rng.set_word_pos(0);
+    rng
+}
+
+/// Fill `net_forces` with the force on each of `blobs`, finding neighbors
+/// through `spatial_hash`. With `wrap`, blobs near opposite edges see each
+/// other across them.
+fn compute_forces(
+    force_set: &ForceSet,
+    spatial_hash: &mut SpatialHash,
+    width: f64,
+    height: f64,
+    wrap: bool,
+    blobs: &[Blob],
+    net_forces: &mut Vec<Vector2f>,
+) {
+    let max_range = force_set.max_range();
+    if let Some(range) = max_range {
+        spatial_hash.rebuild(width, height, range, blobs);
+    }
+
+    net_forces.clear();
+
+    let mut neighbors = Vec::new();
+    let mut images = Vec::new();
+    for (index, blob) in blobs.iter().enumerate() {
+        neighbors.clear();
+        images.clear();
+
+        if max_range.is_some() {
+            if wrap {
+                spatial_hash.for_each_neighbor_wrapped(&blob.position, blobs, width, height, |other, offset| {
+                    if other == index {
+                        return;
+                    }
+
+                    if *offset == Vector2f::ZERO {
+                        neighbors.push(&blobs[other]);
+                    } else {
+                        let mut image = blobs[other].clone();
+                        image.position += offset;
+                        images.push(image);
+                    }
+                });
+            } else {
+                spatial_hash.for_each_neighbor(&blob.position, |other| {
+                    if other != index {
+                        neighbors.push(&blobs[other]);
+                    }
+                });
+            }
+        }
 
-        // 4 bytes per pixel color
-        4 * index
+        let force = if images.is_empty() {
+            force_set.force_on(blob, &neighbors)
+        } else {
+            // Images across an edge only exist for this blob
+            let mut with_images = neighbors.clone();
+            with_images.extend(images.iter());
+            force_set.force_on(blob, &with_images)
+        };
+        net_forces.push(force);
     }
 }
@@ -0,0 +1,311 @@
+//! Run the blob simulation without a window or GPU and dump the results.
+//!
+//! ```text
+//! blobbin-sim [--config config.json] [--set key=value]... [--width 500] [--height 500] [--blobs 10]
+//!             [--ticks 600] [--step 0.016667] [--seed N] [--every 0]
+//!             [--format json|csv] [--output path]
+//!             [--scene path] [--save-scene path] [--record path]
+//! ```
+//!
+//! `--scene` starts from a saved scene instead of random blobs, using the
+//! scene's size, seed and physics config. `--save-scene` writes the final
+//! world out as a scene. `--record` saves every tick as a recording that
+//! `blobbin --replay` can play back. Recordings also hold the inputs that
+//! added the blobs, so they can be rerun from their header. That doesn't work
+//! for blobs loaded from a scene, so `--record` can't be used with `--scene`.
+//!
+//! The config is resolved the same way as for `blobbin`, unless `--config`
+//! is given, in which case only that file and the environment are used.
+//!
+//! With `--every 0` (the default) only the final state is written, otherwise a
+//! snapshot is written every `N` ticks. Output goes to stdout unless `--output`
+//! is given.
+
+use anyhow::{anyhow, bail, Context};
+use serde::Serialize;
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+};
+
+use blobbin::{
+    app::{Blob, BlobId, Recorder, World, WorldInput},
+    common::config::{ConfigLoader, PhysicsConfig},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Json,
+    Csv,
+}
+
+#[derive(Debug)]
+struct Options {
+    config: Option<PathBuf>,
+    overrides: Vec<String>,
+    width: usize,
+    height: usize,
+    blobs: usize,
+    ticks: u64,
+    step: f64,
+    seed: Option<u64>,
+    every: u64,
+    format: Format,
+    output: Option<PathBuf>,
+    scene: Option<PathBuf>,
+    save_scene: Option<PathBuf>,
+    record: Option<PathBuf>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            config: None,
+            overrides: Vec::new(),
+            width: 500,
+            height: 500,
+            blobs: 10,
+            ticks: 600,
+            step: 1.0 / 60.0,
+            seed: None,
+            every: 0,
+            format: Format::Json,
+            output: None,
+            scene: None,
+            save_scene: None,
+            record: None,
+        }
+    }
+}
+
+impl Options {
+    fn from_args(mut args: impl Iterator<Item = String>) -> anyhow::Result<Self> {
+        let mut options = Self::default();
+
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| anyhow!("Missing value for {}", arg))
+            };
+
+            match arg.as_str() {
+                "--config" => options.config = Some(value()?.into()),
+                "--set" => options.overrides.push(value()?),
+                "--width" => options.width = value()?.parse()?,
+                "--height" => options.height = value()?.parse()?,
+                "--blobs" => options.blobs = value()?.parse()?,
+                "--ticks" => options.ticks = value()?.parse()?,
+                "--step" => options.step = value()?.parse()?,
+                "--seed" => options.seed = Some(value()?.parse()?),
+                "--every" => options.every = value()?.parse()?,
+                "--format" => {
+                    options.format = match value()?.as_str() {
+                        "json" => Format::Json,
+                        "csv" => Format::Csv,
+                        other => bail!("Unknown format: {}", other),
+                    }
+                }
+                "--output" => options.output = Some(value()?.into()),
+                "--scene" => options.scene = Some(value()?.into()),
+                "--save-scene" => options.save_scene = Some(value()?.into()),
+                "--record" => options.record = Some(value()?.into()),
+                other => bail!("Unknown argument: {}", other),
+            }
+        }
+
+        if options.step <= 0.0 {
+            bail!("--step must be positive");
+        }
+
+        if options.scene.is_some() && options.record.is_some() {
+            bail!("--record can't be used with --scene, recordings start from an empty world");
+        }
+
+        // Pick the output format from the file extension unless told otherwise
+        if let Some(extension) = options.output.as_ref().and_then(|path| path.extension()) {
+            if extension == "csv" {
+                options.format = Format::Csv;
+            }
+        }
+
+        Ok(options)
+    }
+
+    fn config_loader(&self) -> anyhow::Result<ConfigLoader> {
+        let mut loader = match &self.config {
+            Some(path) => ConfigLoader::new().file(path).env(std::env::vars()),
+            None => ConfigLoader::standard(),
+        };
+
+        for arg in &self.overrides {
+            loader = loader.set_arg(arg)?;
+        }
+
+        Ok(loader)
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct BlobState {
+    id: BlobId,
+    species: usize,
+    x: f64,
+    y: f64,
+    vx: f64,
+    vy: f64,
+}
+
+impl From<&Blob> for BlobState {
+    fn from(blob: &Blob) -> Self {
+        Self {
+            id: blob.id,
+            species: blob.species,
+            x: blob.position.x,
+            y: blob.position.y,
+            vx: blob.velocity.x,
+            vy: blob.velocity.y,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Snapshot {
+    tick: u64,
+    blobs: Vec<BlobState>,
+}
+
+impl From<&World> for Snapshot {
+    fn from(world: &World) -> Self {
+        Self {
+            tick: world.tick(),
+            blobs: world.blobs.iter().map(BlobState::from).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Report {
+    seed: u64,
+    width: usize,
+    height: usize,
+    step: f64,
+    config: PhysicsConfig,
+    snapshots: Vec<Snapshot>,
+}
+
+fn main() -> anyhow::Result<()> {
+    env_logger::init();
+
+    let options = Options::from_args(std::env::args().skip(1))?;
+
+    let mut world = match &options.scene {
+        Some(path) => World::load(path)?,
+        None => {
+            if let Some(path) = options.config.as_ref().filter(|path| !path.exists()) {
+                bail!("{} does not exist", path.display());
+            }
+
+            let config = options
+                .config_loader()?
+                .load()
+                .context("Failed to load config")?
+                .config
+                .physics;
+
+            match options.seed {
+                Some(seed) => World::with_seed(options.width, options.height, config, seed),
+                None => World::new(options.width, options.height, config),
+            }
+        }
+    };
+
+    // Start recording before adding any blobs, so recordings replay from empty
+    let mut recorder = options
+        .record
+        .as_ref()
+        .map(|_| Recorder::new(&world, options.step));
+
+    let mut inputs = Vec::new();
+    if options.scene.is_none() {
+        inputs.push(WorldInput::AddRandomBlobs { count: options.blobs });
+    }
+    for input in &inputs {
+        world.apply_input(input);
+    }
+
+    let mut snapshots = Vec::new();
+    if options.every > 0 {
+        snapshots.push(Snapshot::from(&world));
+    }
+
+    for _ in 0..options.ticks {
+        world.update(options.step);
+        if let Some(recorder) = &mut recorder {
+            recorder.record(&world, &inputs);
+        }
+        inputs.clear();
+
+        if options.every > 0 && world.tick() % options.every == 0 {
+            snapshots.push(Snapshot::from(&world));
+        }
+    }
+
+    if snapshots.last().map(|last| last.tick) != Some(world.tick()) {
+        snapshots.push(Snapshot::from(&world));
+    }
+
+    log::info!(
+        "Simulated {} blobs for {} ticks with seed {}",
+        world.blobs.len(),
+        world.tick(),
+        world.seed()
+    );
+
+    let writer: Box<dyn Write> = match &options.output {
+        Some(path) => Box::new(
+            File::create(path).with_context(|| format!("Failed to create {}", path.display()))?,
+        ),
+        None => Box::new(io::stdout()),
+    };
+    let mut writer = BufWriter::new(writer);
+
+    match options.format {
+        Format::Json => {
+            let report = Report {
+                seed: world.seed(),
+                width: world.width,
+                height: world.height,
+                step: options.step,
+                config: world.config.clone(),
+                snapshots,
+            };
+            serde_json::to_writer_pretty(&mut writer, &report)?;
+            writeln!(writer)?;
+        }
+        Format::Csv => {
+            writeln!(writer, "tick,id,species,x,y,vx,vy")?;
+            for snapshot in &snapshots {
+                for blob in &snapshot.blobs {
+                    writeln!(
+                        writer,
+                        "{},{},{},{},{},{},{}",
+                        snapshot.tick, blob.id, blob.species, blob.x, blob.y, blob.vx, blob.vy
+                    )?;
+                }
+            }
+        }
+    }
+
+    writer.flush()?;
+
+    if let Some(path) = &options.save_scene {
+        world.save(path)?;
+    }
+
+    if let (Some(recorder), Some(path)) = (&recorder, &options.record) {
+        recorder.save(path)?;
+    }
+
+    Ok(())
+}
@@ -1,2 +1,4 @@
 pub mod config;
+pub mod events;
 pub mod math;
+pub mod watch;
@@ -1,46 +1,105 @@
+pub mod camera;
+pub mod format;
+pub mod graphics;
+pub mod loader;
+pub mod macros;
+pub mod physics;
+pub mod validation;
+pub mod watcher;
+pub mod window;
+
+use anyhow::Context;
 use serde::{Deserialize, Serialize};
-use std::{fs::File, io::BufReader, path::Path};
+use std::path::Path;
+
+pub use camera::CameraConfig;
+pub use format::ConfigFormat;
+pub use graphics::{GraphicsConfig, PresentMode};
+pub use loader::{ConfigLoader, ConfigSource, ResolvedConfig};
+pub use physics::{
+    BoundaryConfig, BoundaryMode, CollisionConfig, CollisionShape, ForceConfig, IntegratorKind, PhysicsConfig,
+    SpeciesConfig,
+};
+pub use validation::{ConfigViolation, ValidationError};
+pub use watcher::{ConfigChanged, ConfigWatcher};
+pub use window::WindowConfig;
 
 pub const DEFAULT_CONFIG_FILE_NAME: &str = "config.json";
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Config {
-    pub blob_size: f64,
-    pub repel_force: f64,
-    pub repel_distance: f64,
-    pub friction_force: f64,
-    pub max_acceleration: f64,
-    pub min_acceleration: f64,
-}
+/// Config files are named this plus the extension of their format.
+pub const CONFIG_FILE_STEM: &str = "config";
 
-macro_rules! print_config_diff {
-    ($item:expr, $new:expr) => {
-        if $item != $new {
-            println!("{} => {}", stringify!($item), $new);
-        }
-    };
+/// Everything that can be configured, one section per subsystem.
+///
+/// Missing fields take their value from `Config::default()`, so config files
+/// only need to list what they change.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub window: WindowConfig,
+    pub graphics: GraphicsConfig,
+    pub camera: CameraConfig,
+    pub physics: PhysicsConfig,
 }
 
 impl Config {
+    /// Load a single config file on top of the defaults. The format is picked
+    /// from the file extension.
     pub fn load_file<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
-        let file = File::open(path)?;
-        let reader = BufReader::new(file);
-
-        let config = serde_json::from_reader(reader)?;
+        let path = path.as_ref();
+        if !path.exists() {
+            anyhow::bail!("{} does not exist", path.display());
+        }
 
-        Ok(config)
+        Ok(ConfigLoader::new().file(path).load()?.config)
     }
 
+    /// Resolve the config from the standard system, user and project files
+    /// and the environment. See [`ConfigLoader::standard`].
     pub fn load_default_config_file() -> anyhow::Result<Self> {
-        Config::load_file(DEFAULT_CONFIG_FILE_NAME)
+        Ok(ConfigLoader::standard().load()?.config)
+    }
+
+    /// Save in the format matching the file extension.
+    pub fn save_file<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let contents = ConfigFormat::from_path(path)?.serialize(self)?;
+
+        std::fs::write(path, contents).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Every value that differs between `self` and `new`, keyed the same way
+    /// as `--set`, as `(key, old, new)`.
+    pub fn diff(&self, new: &Config) -> Vec<(String, serde_json::Value, serde_json::Value)> {
+        let old = serde_json::to_value(self).expect("Config always serializes");
+        let new = serde_json::to_value(new).expect("Config always serializes");
+
+        loader::leaf_keys(&old)
+            .into_iter()
+            .filter_map(|key| {
+                let old_value = loader::lookup(&old, &key)?;
+                let new_value = loader::lookup(&new, &key)?;
+                if old_value != new_value {
+                    Some((key, old_value.clone(), new_value.clone()))
+                } else {
+                    None
+                }
+            })
+            .collect()
     }
 
     pub fn print_config_diff(&self, new: &Config) {
-        print_config_diff!(self.blob_size, new.blob_size);
-        print_config_diff!(self.repel_force, new.repel_force);
-        print_config_diff!(self.repel_distance, new.repel_distance);
-        print_config_diff!(self.friction_force, new.friction_force);
-        print_config_diff!(self.max_acceleration, new.max_acceleration);
-        print_config_diff!(self.min_acceleration, new.min_acceleration);
+        for (key, old, new) in self.diff(new) {
+            println!("{}: {} => {}", key, old, new);
+        }
     }
 }
+
+/// Parse a config file of any format without applying it to anything.
+pub(crate) fn read_value(path: &Path) -> anyhow::Result<serde_json::Value> {
+    let format = ConfigFormat::from_path(path)?;
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+
+    format.parse(path, &contents)
+}
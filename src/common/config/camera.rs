@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CameraConfig {
+    /// Vertical field of view, in degrees.
+    pub fovy: f64,
+    pub znear: f64,
+    pub zfar: f64,
+    /// How far the camera moves per frame while a movement key is held.
+    pub speed: f64,
+}
+
+impl Default for CameraConfig {
+    fn default() -> Self {
+        Self {
+            fovy: 45.0,
+            znear: 0.1,
+            zfar: 100.0,
+            speed: 0.2,
+        }
+    }
+}
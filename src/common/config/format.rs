@@ -0,0 +1,87 @@
+use anyhow::bail;
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::Path;
+
+/// A file format config can be read from and saved to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConfigFormat {
+    Json,
+    Toml,
+    Ron,
+}
+
+impl ConfigFormat {
+    pub const ALL: [ConfigFormat; 3] = [ConfigFormat::Json, ConfigFormat::Toml, ConfigFormat::Ron];
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ConfigFormat::Json => "json",
+            ConfigFormat::Toml => "toml",
+            ConfigFormat::Ron => "ron",
+        }
+    }
+
+    /// Pick the format from a file's extension.
+    pub fn from_path(path: &Path) -> anyhow::Result<Self> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or("");
+
+        match Self::ALL
+            .iter()
+            .find(|format| extension.eq_ignore_ascii_case(format.extension()))
+        {
+            Some(&format) => Ok(format),
+            None => bail!(
+                "{}: unknown config format, expected a .json, .toml or .ron file",
+                path.display()
+            ),
+        }
+    }
+
+    /// Parse `contents`, naming `path` and the line and column of any error.
+    pub fn parse<T: DeserializeOwned>(self, path: &Path, contents: &str) -> anyhow::Result<T> {
+        let (line, column, message) = match self {
+            ConfigFormat::Json => match serde_json::from_str(contents) {
+                Ok(value) => return Ok(value),
+                Err(error) => (error.line(), error.column(), error.to_string()),
+            },
+            ConfigFormat::Toml => match toml::from_str(contents) {
+                Ok(value) => return Ok(value),
+                Err(error) => {
+                    // toml counts from zero
+                    let (line, column) = error.line_col().map_or((0, 0), |(line, column)| (line + 1, column + 1));
+                    (line, column, error.to_string())
+                }
+            },
+            // Going through `ron::Value` first lets RON structs deserialize as
+            // maps, which `ron` can't do directly for types like `serde_json::Value`
+            ConfigFormat::Ron => match ron::de::from_str::<ron::Value>(contents) {
+                Ok(value) => {
+                    return T::deserialize(value)
+                        .map_err(|error| anyhow::anyhow!("{}: {}", path.display(), error))
+                }
+                Err(error) => (error.position.line, error.position.col, error.code.to_string()),
+            },
+        };
+
+        bail!("{}:{}:{}: {}", path.display(), line, column, message)
+    }
+
+    pub fn serialize<T: Serialize>(self, value: &T) -> anyhow::Result<String> {
+        let mut contents = match self {
+            ConfigFormat::Json => serde_json::to_string_pretty(value)?,
+            // `toml::Value` writes plain values before tables whatever the
+            // field order, which TOML requires
+            ConfigFormat::Toml => toml::to_string_pretty(&toml::Value::try_from(value)?)?,
+            ConfigFormat::Ron => ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())?,
+        };
+
+        if !contents.ends_with('\n') {
+            contents.push('\n');
+        }
+
+        Ok(contents)
+    }
+}
@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+
+use crate::string_enum;
+
+string_enum! {
+    /// When finished frames are shown, mirroring `wgpu::PresentMode`.
+    pub enum PresentMode as "present mode" {
+        /// Show frames as soon as they are ready, which may tear.
+        Immediate = "immediate",
+        /// Wait for vsync, replacing any frame that is already waiting.
+        Mailbox = "mailbox",
+        /// Wait for vsync, queueing frames. Always supported.
+        Fifo = "fifo",
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GraphicsConfig {
+    /// Linear RGBA, each between 0 and 1.
+    pub clear_color: [f64; 4],
+    pub present_mode: PresentMode,
+    /// Load shaders from `src/graphics/shaders` and rebuild pipelines when
+    /// they change. On by default in debug builds.
+    pub hot_reload_shaders: bool,
+}
+
+impl Default for GraphicsConfig {
+    fn default() -> Self {
+        Self {
+            clear_color: [0.0, 0.0, 0.0, 1.0],
+            present_mode: PresentMode::Fifo,
+            hot_reload_shaders: cfg!(debug_assertions),
+        }
+    }
+}
@@ -0,0 +1,339 @@
+use anyhow::{bail, Context};
+use serde_json::{Map, Value};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use crate::common::config::{self, Config, ConfigFormat};
+
+/// Environment variables starting with this override config values.
+///
+/// Nested keys are joined with a double underscore, so
+/// `BLOBBIN_PHYSICS__REPEL_FORCE=20` sets `physics.repel_force`.
+pub const ENV_PREFIX: &str = "BLOBBIN_";
+
+const APP_DIRECTORY: &str = "blobbin";
+
+/// Keys from before the config was split into sections, which now live in
+/// `physics`. They are still accepted, with a warning.
+const FLAT_PHYSICS_KEYS: &[&str] = &[
+    "blob_size",
+    "repel_force",
+    "repel_distance",
+    "friction_force",
+    "max_acceleration",
+    "min_acceleration",
+];
+
+/// Where the effective value of a config key came from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigSource {
+    Default,
+    File(PathBuf),
+    Env(String),
+    Cli,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigSource::Default => write!(f, "default"),
+            ConfigSource::File(path) => write!(f, "{}", path.display()),
+            ConfigSource::Env(name) => write!(f, "${}", name),
+            ConfigSource::Cli => write!(f, "--set"),
+        }
+    }
+}
+
+/// Builds a `Config` from layers, each overriding the keys it sets in the
+/// layers before it:
+///
+/// 1. Built-in defaults
+/// 2. Config files, in the order they were added. Missing files are skipped.
+/// 3. `BLOBBIN_*` environment variables
+/// 4. `--set key=value` overrides
+///
+/// Files only need to contain the keys they change.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigLoader {
+    files: Vec<PathBuf>,
+    env: Vec<(String, String)>,
+    overrides: Vec<(String, String)>,
+}
+
+impl ConfigLoader {
+    /// No layers besides the defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The system, user and project config files plus the process environment.
+    ///
+    /// Each directory is checked for a `config.json`, `config.toml` and
+    /// `config.ron`, applied in that order.
+    pub fn standard() -> Self {
+        let directories = system_config_dir()
+            .into_iter()
+            .chain(user_config_dir())
+            .chain(Some(PathBuf::new()));
+
+        let mut loader = Self::new();
+        for directory in directories {
+            for format in &ConfigFormat::ALL {
+                let file_name = format!("{}.{}", config::CONFIG_FILE_STEM, format.extension());
+                loader = loader.file(directory.join(file_name));
+            }
+        }
+
+        loader.env(std::env::vars())
+    }
+
+    pub fn file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.files.push(path.into());
+        self
+    }
+
+    /// Use any variables starting with [`ENV_PREFIX`] as overrides. Variables
+    /// that don't name a config key are skipped with a warning.
+    pub fn env(mut self, vars: impl IntoIterator<Item = (String, String)>) -> Self {
+        self.env.extend(
+            vars.into_iter()
+                .filter(|(name, _)| name.starts_with(ENV_PREFIX)),
+        );
+        self
+    }
+
+    pub fn set(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.overrides.push((key.into(), value.into()));
+        self
+    }
+
+    /// Parse a `key=value` override, as passed to `--set`.
+    pub fn set_arg(self, arg: &str) -> anyhow::Result<Self> {
+        match arg.find('=') {
+            Some(index) => Ok(self.set(arg[..index].trim(), arg[index + 1..].trim())),
+            None => bail!("Expected key=value, found {:?}", arg),
+        }
+    }
+
+    /// The config files in this loader, whether or not they exist.
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// Merge every layer and validate the result.
+    pub fn load(&self) -> anyhow::Result<ResolvedConfig> {
+        let mut value = serde_json::to_value(Config::default())?;
+        let mut sources = BTreeMap::new();
+        for key in leaf_keys(&value) {
+            sources.insert(key, ConfigSource::Default);
+        }
+
+        for path in &self.files {
+            if !path.exists() {
+                continue;
+            }
+
+            let mut layer = config::read_value(path)?;
+            let source = ConfigSource::File(path.clone());
+            migrate_flat_keys(&mut layer, &source);
+            merge(&mut value, &layer, "", &source, &mut sources)
+                .with_context(|| format!("Invalid config file {}", path.display()))?;
+        }
+
+        for (name, raw) in &self.env {
+            let key = name[ENV_PREFIX.len()..].to_lowercase().replace("__", ".");
+            let key = migrate_flat_key(&key, &ConfigSource::Env(name.clone())).unwrap_or(key);
+            // Other variables can share the prefix, such as `BLOBBIN_LOG`
+            if lookup(&value, &key).is_none() {
+                log::warn!("Ignoring ${}, {} is not a config key", name, key);
+                continue;
+            }
+            let source = ConfigSource::Env(name.clone());
+            set_key(&mut value, &key, parse_value(raw), &source, &mut sources)?;
+        }
+
+        for (key, raw) in &self.overrides {
+            let key = migrate_flat_key(key, &ConfigSource::Cli).unwrap_or_else(|| key.clone());
+            set_key(&mut value, &key, parse_value(raw), &ConfigSource::Cli, &mut sources)?;
+        }
+
+        let config: Config = serde_json::from_value(value)?;
+        config.validate()?;
+
+        Ok(ResolvedConfig { config, sources })
+    }
+}
+
+/// A loaded `Config`, along with where each of its values came from.
+#[derive(Debug, Clone)]
+pub struct ResolvedConfig {
+    pub config: Config,
+    sources: BTreeMap<String, ConfigSource>,
+}
+
+impl ResolvedConfig {
+    /// `key` uses the same dotted form as `--set`.
+    pub fn source(&self, key: &str) -> Option<&ConfigSource> {
+        self.sources.get(key)
+    }
+}
+
+/// One `key = value  # source` line per config value, as shown by `--print-config`.
+impl fmt::Display for ResolvedConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = serde_json::to_value(&self.config).map_err(|_| fmt::Error)?;
+        let width = self.sources.keys().map(String::len).max().unwrap_or(0);
+
+        for (key, source) in &self.sources {
+            let current = lookup(&value, key).map_or_else(String::new, Value::to_string);
+            let line = format!("{:width$} = {}", key, current, width = width);
+            writeln!(f, "{:40}  # {}", line, source)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn system_config_dir() -> Option<PathBuf> {
+    if cfg!(unix) {
+        Some(Path::new("/etc").join(APP_DIRECTORY))
+    } else {
+        std::env::var_os("PROGRAMDATA").map(|dir| PathBuf::from(dir).join(APP_DIRECTORY))
+    }
+}
+
+fn user_config_dir() -> Option<PathBuf> {
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))?;
+
+    Some(config_dir.join(APP_DIRECTORY))
+}
+
+/// Overrides are parsed as JSON when possible so `--set physics.blob_size=4` is a
+/// number, and taken as a plain string otherwise.
+fn parse_value(raw: &str) -> Value {
+    serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()))
+}
+
+/// Every leaf key in `value`, dotted.
+pub(crate) fn leaf_keys(value: &Value) -> Vec<String> {
+    fn visit(value: &Value, prefix: &str, keys: &mut Vec<String>) {
+        match value {
+            Value::Object(map) => {
+                for (key, child) in map {
+                    visit(child, &join_key(prefix, key), keys);
+                }
+            }
+            _ => keys.push(prefix.to_string()),
+        }
+    }
+
+    let mut keys = Vec::new();
+    visit(value, "", &mut keys);
+    keys
+}
+
+pub(crate) fn lookup<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+    key.split('.').try_fold(value, |value, part| value.get(part))
+}
+
+/// Merge every key in `layer` into `base`. Keys must already exist in `base`,
+/// which catches typos instead of silently ignoring them.
+fn merge(
+    base: &mut Value,
+    layer: &Value,
+    prefix: &str,
+    source: &ConfigSource,
+    sources: &mut BTreeMap<String, ConfigSource>,
+) -> anyhow::Result<()> {
+    let layer = match layer {
+        Value::Object(map) => map,
+        _ if prefix.is_empty() => bail!("Expected a table of config values"),
+        _ => bail!("Expected a table for {}", prefix),
+    };
+
+    for (key, layer_value) in layer {
+        let full_key = join_key(prefix, key);
+        let base_value = base
+            .get_mut(key.as_str())
+            .with_context(|| format!("Unknown config key {}", full_key))?;
+
+        if base_value.is_object() {
+            merge(base_value, layer_value, &full_key, source, sources)?;
+        } else {
+            *base_value = layer_value.clone();
+            sources.insert(full_key, source.clone());
+        }
+    }
+
+    Ok(())
+}
+
+/// The sectioned key for a flat key from before sections existed, or `None`
+/// if `key` isn't one.
+fn migrate_flat_key(key: &str, source: &ConfigSource) -> Option<String> {
+    if !FLAT_PHYSICS_KEYS.contains(&key) {
+        return None;
+    }
+
+    let migrated = join_key("physics", key);
+    log::warn!("{} sets {}, which has moved to {}", source, key, migrated);
+    Some(migrated)
+}
+
+/// Move any flat keys at the top of a config file into their sections.
+/// Values already in a section win over flat ones.
+fn migrate_flat_keys(layer: &mut Value, source: &ConfigSource) {
+    let map = match layer {
+        Value::Object(map) => map,
+        _ => return,
+    };
+
+    for &key in FLAT_PHYSICS_KEYS {
+        let value = match map.remove(key) {
+            Some(value) => value,
+            None => continue,
+        };
+        migrate_flat_key(key, source);
+
+        let physics = map
+            .entry("physics")
+            .or_insert_with(|| Value::Object(Map::new()));
+        if let Value::Object(physics) = physics {
+            physics.entry(key).or_insert(value);
+        }
+    }
+}
+
+fn set_key(
+    base: &mut Value,
+    key: &str,
+    value: Value,
+    source: &ConfigSource,
+    sources: &mut BTreeMap<String, ConfigSource>,
+) -> anyhow::Result<()> {
+    // Build the single-key layer `key` describes and merge it like a file
+    let layer = key
+        .rsplit('.')
+        .fold(value, |value, part| {
+            let mut map = Map::new();
+            map.insert(part.to_string(), value);
+            Value::Object(map)
+        });
+
+    merge(base, &layer, "", source, sources)
+        .with_context(|| format!("Invalid override from {}", source))
+}
+
+fn join_key(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
@@ -0,0 +1,68 @@
+/// Define a config enum of unit variants, each stored as a plain string so it
+/// reads the same in every config format.
+///
+/// Generates `ALL`, `name` and conversions to and from `String`. The string
+/// after `as` names the setting in errors for unknown values.
+#[macro_export]
+macro_rules! string_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident as $what:literal {
+            $(
+                $(#[$variant_meta:meta])*
+                $variant:ident = $string:literal,
+            )+
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+        #[serde(into = "String", try_from = "String")]
+        $vis enum $name {
+            $(
+                $(#[$variant_meta])*
+                $variant,
+            )+
+        }
+
+        impl $name {
+            pub const ALL: &'static [$name] = &[$($name::$variant),+];
+
+            pub fn name(self) -> &'static str {
+                match self {
+                    $($name::$variant => $string,)+
+                }
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> Self {
+                value.name().to_string()
+            }
+        }
+
+        impl std::convert::TryFrom<String> for $name {
+            type Error = String;
+
+            fn try_from(name: String) -> Result<Self, Self::Error> {
+                Self::ALL.iter().copied().find(|value| value.name() == name).ok_or_else(|| {
+                    let names: Vec<&str> = Self::ALL.iter().map(|value| value.name()).collect();
+                    format!(
+                        "unknown {} {:?}, expected {}",
+                        $what,
+                        name,
+                        $crate::common::config::macros::one_of(&names)
+                    )
+                })
+            }
+        }
+    };
+}
+
+/// `a, b or c`.
+pub fn one_of(names: &[&str]) -> String {
+    match names.split_last() {
+        Some((last, [])) => last.to_string(),
+        Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+        None => String::new(),
+    }
+}
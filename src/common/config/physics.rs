@@ -0,0 +1,283 @@
+use std::fmt;
+
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::string_enum;
+
+/// How blobs move and push each other around.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PhysicsConfig {
+    pub blob_size: f64,
+    /// Shorthand for a `repulsion` force. Set to 0 to turn it off.
+    pub repel_force: f64,
+    pub repel_distance: f64,
+    /// Shorthand for a `drag` force. Set to 0 to turn it off.
+    pub friction_force: f64,
+    pub max_acceleration: f64,
+    /// Weaker accelerations are dropped, so blobs can come to rest.
+    pub min_acceleration: f64,
+    /// How blobs are moved each tick.
+    pub integrator: IntegratorKind,
+    /// How far species interactions reach.
+    pub interaction_range: f64,
+    /// Forces applied on top of the repulsion and drag above.
+    pub forces: Vec<ForceConfig>,
+    /// Kinds of blob. Without any, every blob is `blob_size` and white.
+    pub species: Vec<SpeciesConfig>,
+    pub collisions: CollisionConfig,
+    pub boundary: BoundaryConfig,
+}
+
+/// A kind of blob, with its own size, mass, color and attitude to every other
+/// species.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpeciesConfig {
+    pub name: String,
+    pub size: f64,
+    /// Defaults to `(size / blob_size)²`, so blobs of `blob_size` have a mass
+    /// of 1 and bigger blobs are heavier in proportion to their area.
+    pub mass: Option<f64>,
+    /// Linear RGB, each between 0 and 1.
+    pub color: [f64; 3],
+    /// How strongly blobs of this species are pulled towards each species, in
+    /// the same order as `physics.species`. Negative values push away instead,
+    /// and missing values are 0.
+    pub interactions: Vec<f64>,
+}
+
+impl Default for SpeciesConfig {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            size: 5.0,
+            mass: None,
+            color: [1.0, 1.0, 1.0],
+            interactions: Vec::new(),
+        }
+    }
+}
+
+/// How blobs bump into each other.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CollisionConfig {
+    /// Turn off to let blobs overlap, only pushed apart by forces.
+    pub enabled: bool,
+    pub shape: CollisionShape,
+    /// How much of their closing speed colliding blobs bounce back with, from 0
+    /// to 1.
+    pub restitution: f64,
+    /// How strongly touching blobs resist sliding past each other.
+    pub friction: f64,
+}
+
+impl Default for CollisionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            shape: CollisionShape::Circle,
+            restitution: 0.5,
+            friction: 0.1,
+        }
+    }
+}
+
+string_enum! {
+    /// The shape blobs collide as.
+    pub enum CollisionShape as "collision shape" {
+        /// The circle each blob is drawn as.
+        Circle = "circle",
+        /// The square around each blob.
+        Aabb = "aabb",
+    }
+}
+
+/// What happens to blobs at the edges of the world.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BoundaryConfig {
+    pub mode: BoundaryMode,
+    /// How much of their speed blobs keep when bouncing off an edge, from 0
+    /// to 1. Only used by `reflect`.
+    pub restitution: f64,
+}
+
+impl Default for BoundaryConfig {
+    fn default() -> Self {
+        Self {
+            mode: BoundaryMode::Reflect,
+            restitution: 1.0,
+        }
+    }
+}
+
+string_enum! {
+    /// How the edges of the world treat blobs.
+    pub enum BoundaryMode as "boundary mode" {
+        /// Bounce off the edges.
+        Reflect = "reflect",
+        /// Leave one edge and come back in at the opposite one. Blobs near
+        /// opposite edges also push each other across them.
+        Wrap = "wrap",
+        /// Remove blobs once their center leaves the world.
+        Absorb = "absorb",
+        /// No edges at all.
+        Unbounded = "unbounded",
+    }
+}
+
+string_enum! {
+    /// A numerical integrator for moving blobs.
+    pub enum IntegratorKind as "integrator" {
+        /// Move, then accelerate. Cheapest, but gains energy over time.
+        ExplicitEuler = "explicit_euler",
+        /// Accelerate, then move with the new velocity.
+        SemiImplicitEuler = "semi_implicit_euler",
+        /// Second order, and keeps energy steady for forces that only depend on position.
+        VelocityVerlet = "velocity_verlet",
+        /// Fourth order Runge-Kutta. The most accurate, at four force evaluations a tick.
+        Rk4 = "rk4",
+    }
+}
+
+/// One force acting on blobs, written as a table with a `type` field, e.g.
+/// `{ "type": "wind", "x": 2.0, "y": 0.0 }`.
+///
+/// Binary formats can't read tagged enums back, so there it's read as the type
+/// followed by the values in the order they're declared, the way it's written.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(remote = "Self", tag = "type", rename_all = "snake_case")]
+pub enum ForceConfig {
+    /// Push away from every blob within `range`.
+    Repulsion { strength: f64, range: f64 },
+    /// Pull towards every blob within `range`.
+    Attraction { strength: f64, range: f64 },
+    /// Repel up close and attract further out, settling `sigma` apart.
+    LennardJones { epsilon: f64, sigma: f64, range: f64 },
+    /// Pull towards the point `(x, y)`.
+    Gravity { x: f64, y: f64, strength: f64 },
+    /// Steer towards the average velocity of blobs within `range`.
+    Alignment { strength: f64, range: f64 },
+    /// Steer towards the center of the blobs within `range`.
+    Cohesion { strength: f64, range: f64 },
+    /// Steer away from blobs within `range`, harder the closer they are.
+    Separation { strength: f64, range: f64 },
+    /// A constant push in one direction.
+    Wind { x: f64, y: f64 },
+    /// Slow blobs down in proportion to their speed.
+    Drag { coefficient: f64 },
+}
+
+const FORCE_TYPES: &[&str] = &[
+    "repulsion",
+    "attraction",
+    "lennard_jones",
+    "gravity",
+    "alignment",
+    "cohesion",
+    "separation",
+    "wind",
+    "drag",
+];
+
+impl Serialize for ForceConfig {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ForceConfig::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ForceConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            ForceConfig::deserialize(deserializer)
+        } else {
+            // The type and at most three values
+            deserializer.deserialize_tuple(4, BinaryForceVisitor)
+        }
+    }
+}
+
+struct BinaryForceVisitor;
+
+impl<'de> Visitor<'de> for BinaryForceVisitor {
+    type Value = ForceConfig;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a force type followed by its values")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<ForceConfig, A::Error> {
+        let kind: String = next_element(&mut seq, 0)?;
+        let mut value = |index| next_element::<A, f64>(&mut seq, index);
+        Ok(match kind.as_str() {
+            "repulsion" => ForceConfig::Repulsion {
+                strength: value(1)?,
+                range: value(2)?,
+            },
+            "attraction" => ForceConfig::Attraction {
+                strength: value(1)?,
+                range: value(2)?,
+            },
+            "lennard_jones" => ForceConfig::LennardJones {
+                epsilon: value(1)?,
+                sigma: value(2)?,
+                range: value(3)?,
+            },
+            "gravity" => ForceConfig::Gravity {
+                x: value(1)?,
+                y: value(2)?,
+                strength: value(3)?,
+            },
+            "alignment" => ForceConfig::Alignment {
+                strength: value(1)?,
+                range: value(2)?,
+            },
+            "cohesion" => ForceConfig::Cohesion {
+                strength: value(1)?,
+                range: value(2)?,
+            },
+            "separation" => ForceConfig::Separation {
+                strength: value(1)?,
+                range: value(2)?,
+            },
+            "wind" => ForceConfig::Wind {
+                x: value(1)?,
+                y: value(2)?,
+            },
+            "drag" => ForceConfig::Drag {
+                coefficient: value(1)?,
+            },
+            other => return Err(de::Error::unknown_variant(other, FORCE_TYPES)),
+        })
+    }
+}
+
+fn next_element<'de, A: SeqAccess<'de>, T: Deserialize<'de>>(
+    seq: &mut A,
+    index: usize,
+) -> Result<T, A::Error> {
+    seq.next_element()?
+        .ok_or_else(|| de::Error::invalid_length(index, &BinaryForceVisitor))
+}
+
+impl Default for PhysicsConfig {
+    fn default() -> Self {
+        Self {
+            blob_size: 5.0,
+            repel_force: 15.0,
+            repel_distance: 10.0,
+            friction_force: 1.0,
+            max_acceleration: 10000.0,
+            min_acceleration: 0.001,
+            integrator: IntegratorKind::SemiImplicitEuler,
+            interaction_range: 30.0,
+            forces: Vec::new(),
+            species: Vec::new(),
+            collisions: CollisionConfig::default(),
+            boundary: BoundaryConfig::default(),
+        }
+    }
+}
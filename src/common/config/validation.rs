@@ -0,0 +1,199 @@
+use std::fmt;
+
+use crate::common::config::{Config, ForceConfig};
+
+/// A single config value that is out of range or inconsistent with another.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigViolation {
+    pub key: String,
+    pub message: String,
+}
+
+/// Every problem found with a config, so they can all be fixed in one go.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    pub violations: Vec<ConfigViolation>,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = self.violations.len();
+        write!(f, "{} invalid config value{}", count, if count == 1 { "" } else { "s" })?;
+
+        for violation in &self.violations {
+            write!(f, "\n  {}: {}", violation.key, violation.message)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Collects violations while checking a config.
+#[derive(Debug, Default)]
+struct Validator {
+    violations: Vec<ConfigViolation>,
+}
+
+impl Validator {
+    fn fail(&mut self, key: &str, message: String) {
+        self.violations.push(ConfigViolation {
+            key: key.to_string(),
+            message,
+        });
+    }
+
+    fn finite(&mut self, key: &str, value: f64) -> bool {
+        if !value.is_finite() {
+            self.fail(key, format!("must be a finite number, found {}", value));
+            return false;
+        }
+        true
+    }
+
+    fn positive(&mut self, key: &str, value: f64) {
+        if self.finite(key, value) && value <= 0.0 {
+            self.fail(key, format!("must be greater than 0, found {}", value));
+        }
+    }
+
+    fn non_negative(&mut self, key: &str, value: f64) {
+        if self.finite(key, value) && value < 0.0 {
+            self.fail(key, format!("must be at least 0, found {}", value));
+        }
+    }
+
+    fn fraction(&mut self, key: &str, value: f64) {
+        if self.finite(key, value) && !(0.0..=1.0).contains(&value) {
+            self.fail(key, format!("must be between 0 and 1, found {}", value));
+        }
+    }
+}
+
+impl Config {
+    /// Check every value is in range and consistent with the others.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let mut validator = Validator::default();
+
+        let window = &self.window;
+        validator.positive("window.width", window.width as f64);
+        validator.positive("window.height", window.height as f64);
+
+        for &component in &self.graphics.clear_color {
+            if !(0.0..=1.0).contains(&component) {
+                validator.fail(
+                    "graphics.clear_color",
+                    format!("components must be between 0 and 1, found {}", component),
+                );
+                break;
+            }
+        }
+
+        let camera = &self.camera;
+        validator.positive("camera.fovy", camera.fovy);
+        if camera.fovy >= 180.0 {
+            validator.fail("camera.fovy", format!("must be less than 180, found {}", camera.fovy));
+        }
+        validator.positive("camera.znear", camera.znear);
+        if validator.finite("camera.zfar", camera.zfar) && camera.zfar <= camera.znear {
+            validator.fail(
+                "camera.zfar",
+                format!("must be greater than znear ({} <= {})", camera.zfar, camera.znear),
+            );
+        }
+        validator.non_negative("camera.speed", camera.speed);
+
+        let physics = &self.physics;
+        validator.positive("physics.blob_size", physics.blob_size);
+        validator.non_negative("physics.repel_force", physics.repel_force);
+        validator.positive("physics.repel_distance", physics.repel_distance);
+        validator.non_negative("physics.friction_force", physics.friction_force);
+        validator.positive("physics.max_acceleration", physics.max_acceleration);
+        validator.non_negative("physics.min_acceleration", physics.min_acceleration);
+
+        if physics.min_acceleration > physics.max_acceleration {
+            validator.fail(
+                "physics.min_acceleration",
+                format!(
+                    "must not be greater than max_acceleration ({} > {})",
+                    physics.min_acceleration, physics.max_acceleration
+                ),
+            );
+        }
+
+        validator.positive("physics.interaction_range", physics.interaction_range);
+        for (index, species) in physics.species.iter().enumerate() {
+            let key = |field: &str| format!("physics.species[{}].{}", index, field);
+
+            validator.positive(&key("size"), species.size);
+            if let Some(mass) = species.mass {
+                validator.positive(&key("mass"), mass);
+            }
+            if let Some(component) = species.color.iter().find(|component| !(0.0..=1.0).contains(*component)) {
+                validator.fail(
+                    &key("color"),
+                    format!("components must be between 0 and 1, found {}", component),
+                );
+            }
+
+            if species.interactions.len() > physics.species.len() {
+                validator.fail(
+                    &key("interactions"),
+                    format!(
+                        "has {} values but there are only {} species",
+                        species.interactions.len(),
+                        physics.species.len()
+                    ),
+                );
+            }
+            if let Some(&strength) = species.interactions.iter().find(|strength| !strength.is_finite()) {
+                validator.finite(&key("interactions"), strength);
+            }
+        }
+
+        validator.fraction("physics.collisions.restitution", physics.collisions.restitution);
+        validator.non_negative("physics.collisions.friction", physics.collisions.friction);
+        validator.fraction("physics.boundary.restitution", physics.boundary.restitution);
+
+        for (index, force) in physics.forces.iter().enumerate() {
+            let key = |field: &str| format!("physics.forces[{}].{}", index, field);
+
+            match *force {
+                ForceConfig::Repulsion { strength, range }
+                | ForceConfig::Attraction { strength, range }
+                | ForceConfig::Alignment { strength, range }
+                | ForceConfig::Cohesion { strength, range }
+                | ForceConfig::Separation { strength, range } => {
+                    validator.non_negative(&key("strength"), strength);
+                    validator.positive(&key("range"), range);
+                }
+                ForceConfig::LennardJones { epsilon, sigma, range } => {
+                    validator.non_negative(&key("epsilon"), epsilon);
+                    validator.positive(&key("sigma"), sigma);
+                    validator.positive(&key("range"), range);
+                }
+                ForceConfig::Gravity { x, y, strength } => {
+                    validator.finite(&key("x"), x);
+                    validator.finite(&key("y"), y);
+                    validator.non_negative(&key("strength"), strength);
+                }
+                ForceConfig::Wind { x, y } => {
+                    validator.finite(&key("x"), x);
+                    validator.finite(&key("y"), y);
+                }
+                ForceConfig::Drag { coefficient } => {
+                    validator.non_negative(&key("coefficient"), coefficient);
+                }
+            }
+        }
+
+        if validator.violations.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError {
+                violations: validator.violations,
+            })
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use std::path::PathBuf;
+
+use crate::common::{
+    config::{Config, ConfigLoader},
+    events::{EventChannel, Subscriber},
+    watch::{self, FileWatcher},
+};
+
+/// Published whenever the config file changes and the new contents are valid.
+#[derive(Debug, Clone)]
+pub struct ConfigChanged {
+    pub previous: Config,
+    pub current: Config,
+}
+
+/// Re-resolves the config as soon as any of a loader's files change on disk.
+///
+/// Invalid files are reported and otherwise ignored, so subscribers only ever
+/// see configs that loaded successfully.
+pub struct ConfigWatcher {
+    loader: ConfigLoader,
+    watched: Vec<PathBuf>,
+    watcher: FileWatcher,
+    current: Config,
+    events: EventChannel<ConfigChanged>,
+}
+
+impl ConfigWatcher {
+    /// Files in directories that don't exist yet can't be watched and are skipped.
+    pub fn new(loader: ConfigLoader, current: Config) -> anyhow::Result<Self> {
+        let mut watcher = FileWatcher::new()?;
+        let mut watched = Vec::new();
+
+        for path in loader.files() {
+            let parent_exists = match path.parent() {
+                Some(parent) if parent != std::path::Path::new("") => parent.is_dir(),
+                _ => true,
+            };
+
+            if parent_exists {
+                watcher.watch_file(path)?;
+                watched.push(watch::resolve_file(path));
+            }
+        }
+
+        if watched.is_empty() {
+            anyhow::bail!("None of the config files can be watched");
+        }
+
+        Ok(Self {
+            loader,
+            watched,
+            watcher,
+            current,
+            events: EventChannel::new(),
+        })
+    }
+
+    /// The config files being watched.
+    pub fn files(&self) -> &[PathBuf] {
+        &self.watched
+    }
+
+    /// The last config that loaded successfully.
+    pub fn current(&self) -> &Config {
+        &self.current
+    }
+
+    pub fn subscribe(&mut self) -> Subscriber<ConfigChanged> {
+        self.events.subscribe()
+    }
+
+    /// Reload the config if any file changed, publishing a [`ConfigChanged`]
+    /// event if it loaded and differs from the current one.
+    ///
+    /// Returns `None` if no file changed, otherwise whether the config loaded.
+    pub fn poll(&mut self) -> Option<anyhow::Result<()>> {
+        let watched = &self.watched;
+        let changed = self
+            .watcher
+            .changed_paths()
+            .iter()
+            .any(|path| watched.contains(&watch::resolve_file(path)));
+
+        if !changed {
+            return None;
+        }
+
+        let config = match self.loader.load() {
+            Ok(resolved) => resolved.config,
+            Err(error) => {
+                log::error!("Ignoring invalid config: {:#}", error);
+                return Some(Err(error));
+            }
+        };
+
+        if config != self.current {
+            log::info!("Reloaded config");
+            self.current.print_config_diff(&config);
+
+            let previous = std::mem::replace(&mut self.current, config);
+            self.events.publish(ConfigChanged {
+                previous,
+                current: self.current.clone(),
+            });
+        }
+
+        Some(Ok(()))
+    }
+}
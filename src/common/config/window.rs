@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowConfig {
+    pub title: String,
+    /// Also the size of the world, in logical pixels.
+    pub width: u32,
+    pub height: u32,
+    pub resizable: bool,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self {
+            title: String::from("Blobbin"),
+            width: 500,
+            height: 500,
+            resizable: true,
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use std::sync::mpsc::{self, Receiver, Sender};
+
+/// Broadcasts events to any number of subscribers.
+///
+/// Subscribers drain their events whenever suits them, so a subsystem can
+/// apply changes at a safe point (e.g. between simulation ticks) rather than
+/// in the middle of whatever published them.
+pub struct EventChannel<E> {
+    subscribers: Vec<Sender<E>>,
+}
+
+impl<E> Default for EventChannel<E> {
+    fn default() -> Self {
+        Self {
+            subscribers: Vec::new(),
+        }
+    }
+}
+
+impl<E: Clone> EventChannel<E> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn subscribe(&mut self) -> Subscriber<E> {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribers.push(sender);
+        Subscriber { receiver }
+    }
+
+    /// Send `event` to every subscriber, forgetting any that have been dropped.
+    pub fn publish(&mut self, event: E) {
+        self.subscribers
+            .retain(|subscriber| subscriber.send(event.clone()).is_ok());
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.subscribers.len()
+    }
+}
+
+pub struct Subscriber<E> {
+    receiver: Receiver<E>,
+}
+
+impl<E> Subscriber<E> {
+    /// Every event published since the last call, oldest first.
+    pub fn drain(&self) -> impl Iterator<Item = E> + '_ {
+        self.receiver.try_iter()
+    }
+
+    /// Only the most recent event published since the last call.
+    pub fn latest(&self) -> Option<E> {
+        self.drain().last()
+    }
+}
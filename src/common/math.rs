@@ -2,6 +2,7 @@ pub mod grid;
 pub mod macros;
 pub mod vector2;
 
+pub use grid::Grid;
 pub use vector2::{Vector2, Vector2f};
 
 #[inline]
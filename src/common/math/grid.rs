@@ -1,19 +1,54 @@
+#[derive(Debug, Clone)]
 pub struct Grid<T> {
     pub width: usize,
     pub height: usize,
     cells: Vec<T>,
 }
 
-impl<T> Grid<T> {
+impl<T: Default> Grid<T> {
     pub fn new(width: usize, height: usize) -> Self {
+        let mut cells = Vec::with_capacity(width * height);
+        cells.resize_with(width * height, Default::default);
+
         Self {
             width,
             height,
-            cells: Vec::new(),
+            cells,
         }
     }
 }
 
+impl<T> Grid<T> {
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        if self.in_bounds(x, y) {
+            Some(&self[(x, y)])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        if self.in_bounds(x, y) {
+            Some(&mut self[(x, y)])
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    pub fn in_bounds(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    pub fn cells_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.cells.iter_mut()
+    }
+}
+
 impl<T, I: Into<(usize, usize)>> std::ops::Index<I> for Grid<T> {
     type Output = T;
 
@@ -34,7 +69,7 @@ impl<T> Grid<T> {
     #[inline]
     fn _index_to_position(&self, index: usize) -> (usize, usize) {
         let x = index % self.width;
-        let y = index / self.height;
+        let y = index / self.width;
 
         (x, y)
     }
@@ -42,6 +77,6 @@ impl<T> Grid<T> {
     #[inline]
     fn position_to_index(&self, position: impl Into<(usize, usize)>) -> usize {
         let position = position.into();
-        position.0 * self.height + position.1
+        position.1 * self.width + position.0
     }
 }
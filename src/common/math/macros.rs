@@ -163,6 +163,10 @@ macro_rules! vector2_impl {
                 self.vector_to(other).magnitude()
             }
 
+            pub fn dot(&self, other: &Vector2<$impl_type>) -> $impl_type {
+                self.x * other.x + self.y * other.y
+            }
+
             pub fn vector_to(&self, target: &Vector2<$impl_type>) -> Vector2<$impl_type> {
                 self - target
             }
@@ -1,9 +1,11 @@
+use serde::{Deserialize, Serialize};
+
 use crate::vector2_impl;
 
 pub type Vector2f = Vector2<f64>;
 pub type Vector2i = Vector2<i32>;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Vector2<T> {
     pub x: T,
     pub y: T,
@@ -45,4 +47,18 @@ impl Vector2<f64> {
     pub fn with_magnitude(&self, magnitude: f64) -> Vector2f {
         magnitude * self.normalized()
     }
+
+    /// This vector shortened to at most `max` long, or zero if it's shorter
+    /// than `min`, so anything too small to matter is dropped.
+    pub fn clamp_magnitude(&self, min: f64, max: f64) -> Vector2f {
+        let magnitude = self.magnitude();
+
+        if magnitude < min {
+            Vector2f::ZERO
+        } else if magnitude > max {
+            self.with_magnitude(max)
+        } else {
+            self.clone()
+        }
+    }
 }
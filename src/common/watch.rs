@@ -0,0 +1,94 @@
+use anyhow::Context;
+use notify::{DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver},
+    time::Duration,
+};
+
+/// How long to wait for a burst of file system events to settle before
+/// reporting a change. Editors often write a file in several steps.
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Watches files and directories for changes without blocking.
+pub struct FileWatcher {
+    watcher: RecommendedWatcher,
+    events: Receiver<DebouncedEvent>,
+}
+
+impl FileWatcher {
+    pub fn new() -> anyhow::Result<Self> {
+        Self::with_debounce(DEFAULT_DEBOUNCE)
+    }
+
+    pub fn with_debounce(delay: Duration) -> anyhow::Result<Self> {
+        let (sender, events) = mpsc::channel();
+        let watcher = notify::watcher(sender, delay).context("Failed to create file watcher")?;
+
+        Ok(Self { watcher, events })
+    }
+
+    /// Directories are watched recursively.
+    pub fn watch(&mut self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        self.watcher
+            .watch(path, RecursiveMode::Recursive)
+            .with_context(|| format!("Failed to watch {}", path.display()))
+    }
+
+    /// Watch a single file.
+    ///
+    /// The file's directory is watched rather than the file itself, since many
+    /// editors save by replacing the file. Callers should filter
+    /// [`FileWatcher::changed_paths`] by comparing them to [`resolve_file`]
+    /// of the path.
+    pub fn watch_file(&mut self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+
+        self.watcher
+            .watch(directory(path), RecursiveMode::NonRecursive)
+            .with_context(|| format!("Failed to watch {}", path.display()))
+    }
+
+    /// Every path that was created, written or renamed into place since the
+    /// last call, without duplicates.
+    pub fn changed_paths(&self) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = Vec::new();
+
+        for event in self.events.try_iter() {
+            let path = match event {
+                DebouncedEvent::Create(path)
+                | DebouncedEvent::Write(path)
+                | DebouncedEvent::Rename(_, path) => path,
+                DebouncedEvent::Error(error, path) => {
+                    log::warn!("File watcher error for {:?}: {}", path, error);
+                    continue;
+                }
+                _ => continue,
+            };
+
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+
+        paths
+    }
+}
+
+/// `path` with its directory made absolute and its links resolved, to match
+/// how changed paths are reported. The file itself doesn't need to exist, and
+/// `path` is returned as is if its directory doesn't either.
+pub fn resolve_file(path: &Path) -> PathBuf {
+    match (directory(path).canonicalize(), path.file_name()) {
+        (Ok(directory), Some(name)) => directory.join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+fn directory(path: &Path) -> &Path {
+    match path.parent() {
+        Some(parent) if parent != Path::new("") => parent,
+        _ => Path::new("."),
+    }
+}
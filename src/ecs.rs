@@ -1,3 +1,11 @@
 pub mod component;
 pub mod entity;
+pub mod query;
 pub mod system;
+pub mod world;
+
+pub use component::{Component, SparseSetStorage, Storage, VecStorage};
+pub use entity::{Entity, EntityAllocator};
+pub use query::{Query, Read, Write};
+pub use system::{Schedule, System};
+pub use world::World;
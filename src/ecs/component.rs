@@ -0,0 +1,171 @@
+/// Data that can be attached to an entity.
+///
+/// Each component picks how it is stored: [`VecStorage`] for components most
+/// entities have, [`SparseSetStorage`] for components only a few entities have.
+///
+/// ```ignore
+/// struct Velocity(Vector2f);
+///
+/// impl Component for Velocity {
+///     type Storage = VecStorage<Self>;
+/// }
+/// ```
+pub trait Component: Sized + 'static {
+    type Storage: Storage<Self>;
+}
+
+/// Component data keyed by entity index.
+///
+/// Storages don't know about generations; the `ecs::World` removes an entity's
+/// components when it is despawned, so a stored index is always current.
+pub trait Storage<T>: Default + 'static {
+    fn insert(&mut self, index: u32, component: T) -> Option<T>;
+    fn remove(&mut self, index: u32) -> Option<T>;
+    fn get(&self, index: u32) -> Option<&T>;
+    fn get_mut(&mut self, index: u32) -> Option<&mut T>;
+    fn len(&self) -> usize;
+
+    fn contains(&self, index: u32) -> bool {
+        self.get(index).is_some()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// One slot per entity index. Fast lookups, but uses memory for every entity.
+pub struct VecStorage<T> {
+    data: Vec<Option<T>>,
+    len: usize,
+}
+
+impl<T> Default for VecStorage<T> {
+    fn default() -> Self {
+        Self {
+            data: Vec::new(),
+            len: 0,
+        }
+    }
+}
+
+impl<T: 'static> Storage<T> for VecStorage<T> {
+    fn insert(&mut self, index: u32, component: T) -> Option<T> {
+        let index = index as usize;
+        if index >= self.data.len() {
+            self.data.resize_with(index + 1, || None);
+        }
+
+        let previous = self.data[index].replace(component);
+        if previous.is_none() {
+            self.len += 1;
+        }
+        previous
+    }
+
+    fn remove(&mut self, index: u32) -> Option<T> {
+        let removed = self.data.get_mut(index as usize).and_then(Option::take);
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    fn get(&self, index: u32) -> Option<&T> {
+        self.data.get(index as usize).and_then(Option::as_ref)
+    }
+
+    fn get_mut(&mut self, index: u32) -> Option<&mut T> {
+        self.data.get_mut(index as usize).and_then(Option::as_mut)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+/// Components packed densely with a sparse index lookup. Memory scales with
+/// the number of components rather than the number of entities.
+pub struct SparseSetStorage<T> {
+    sparse: Vec<Option<u32>>,
+    dense: Vec<T>,
+    indices: Vec<u32>,
+}
+
+impl<T> Default for SparseSetStorage<T> {
+    fn default() -> Self {
+        Self {
+            sparse: Vec::new(),
+            dense: Vec::new(),
+            indices: Vec::new(),
+        }
+    }
+}
+
+impl<T> SparseSetStorage<T> {
+    /// The packed components, in no particular order.
+    pub fn components(&self) -> &[T] {
+        &self.dense
+    }
+
+    /// The entity index of each component in `components`.
+    pub fn indices(&self) -> &[u32] {
+        &self.indices
+    }
+
+    #[inline]
+    fn dense_index(&self, index: u32) -> Option<usize> {
+        self.sparse
+            .get(index as usize)
+            .copied()
+            .flatten()
+            .map(|dense| dense as usize)
+    }
+}
+
+impl<T: 'static> Storage<T> for SparseSetStorage<T> {
+    fn insert(&mut self, index: u32, component: T) -> Option<T> {
+        if let Some(dense) = self.dense_index(index) {
+            return Some(std::mem::replace(&mut self.dense[dense], component));
+        }
+
+        if index as usize >= self.sparse.len() {
+            self.sparse.resize(index as usize + 1, None);
+        }
+
+        self.sparse[index as usize] = Some(self.dense.len() as u32);
+        self.dense.push(component);
+        self.indices.push(index);
+
+        None
+    }
+
+    fn remove(&mut self, index: u32) -> Option<T> {
+        let dense = self.dense_index(index)?;
+        self.sparse[index as usize] = None;
+
+        // Fill the hole with the last component and point its entity at the new slot
+        let removed = self.dense.swap_remove(dense);
+        self.indices.swap_remove(dense);
+        if let Some(&moved) = self.indices.get(dense) {
+            self.sparse[moved as usize] = Some(dense as u32);
+        }
+
+        Some(removed)
+    }
+
+    fn get(&self, index: u32) -> Option<&T> {
+        self.dense_index(index).map(|dense| &self.dense[dense])
+    }
+
+    fn get_mut(&mut self, index: u32) -> Option<&mut T> {
+        match self.dense_index(index) {
+            Some(dense) => Some(&mut self.dense[dense]),
+            None => None,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.dense.len()
+    }
+}
@@ -1,2 +1,100 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct Entity(usize);
+/// A handle to an entity in an `ecs::World`.
+///
+/// The generation is bumped every time an index is reused, so a handle to a
+/// despawned entity never refers to whatever was spawned in its place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Entity {
+    index: u32,
+    generation: u32,
+}
+
+impl Entity {
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+/// Hands out entity handles, recycling the indices of dead entities.
+#[derive(Debug, Default)]
+pub struct EntityAllocator {
+    generations: Vec<u32>,
+    alive: Vec<bool>,
+    free: Vec<u32>,
+    len: usize,
+}
+
+impl EntityAllocator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn allocate(&mut self) -> Entity {
+        self.len += 1;
+
+        match self.free.pop() {
+            Some(index) => {
+                self.alive[index as usize] = true;
+                Entity {
+                    index,
+                    generation: self.generations[index as usize],
+                }
+            }
+            None => {
+                let index = self.generations.len() as u32;
+                self.generations.push(0);
+                self.alive.push(true);
+                Entity {
+                    index,
+                    generation: 0,
+                }
+            }
+        }
+    }
+
+    /// Returns `false` if the entity was already dead.
+    pub fn deallocate(&mut self, entity: Entity) -> bool {
+        if !self.is_alive(entity) {
+            return false;
+        }
+
+        let index = entity.index as usize;
+        self.alive[index] = false;
+        self.generations[index] = self.generations[index].wrapping_add(1);
+        self.free.push(entity.index);
+        self.len -= 1;
+
+        true
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        let index = entity.index as usize;
+        index < self.generations.len()
+            && self.alive[index]
+            && self.generations[index] == entity.generation
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Every live entity, in index order.
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.alive
+            .iter()
+            .zip(&self.generations)
+            .enumerate()
+            .filter(|(_, (alive, _))| **alive)
+            .map(|(index, (_, generation))| Entity {
+                index: index as u32,
+                generation: *generation,
+            })
+    }
+}
@@ -0,0 +1,82 @@
+use std::{
+    cell::{Ref, RefMut},
+    marker::PhantomData,
+};
+
+use crate::ecs::{Component, Storage, World};
+
+/// A set of components to fetch for each entity in `ecs::World::query`.
+///
+/// Implemented for [`Read`], [`Write`] and tuples of up to eight queries.
+pub trait Query {
+    /// The storages borrowed for the duration of the query.
+    type Fetch<'w>;
+    /// The components handed to the query callback for one entity.
+    type Item<'f>;
+
+    /// Returns `None` if any component type has no storage yet.
+    fn fetch(world: &World) -> Option<Self::Fetch<'_>>;
+
+    /// Returns `None` if the entity is missing any of the components.
+    fn get<'f>(fetch: &'f mut Self::Fetch<'_>, index: u32) -> Option<Self::Item<'f>>;
+}
+
+/// Shared access to a component in a query.
+pub struct Read<T>(PhantomData<T>);
+
+/// Exclusive access to a component in a query.
+pub struct Write<T>(PhantomData<T>);
+
+impl<T: Component> Query for Read<T> {
+    type Fetch<'w> = Ref<'w, T::Storage>;
+    type Item<'f> = &'f T;
+
+    fn fetch(world: &World) -> Option<Self::Fetch<'_>> {
+        world.storage::<T>()
+    }
+
+    fn get<'f>(fetch: &'f mut Self::Fetch<'_>, index: u32) -> Option<Self::Item<'f>> {
+        fetch.get(index)
+    }
+}
+
+impl<T: Component> Query for Write<T> {
+    type Fetch<'w> = RefMut<'w, T::Storage>;
+    type Item<'f> = &'f mut T;
+
+    fn fetch(world: &World) -> Option<Self::Fetch<'_>> {
+        world.storage_mut::<T>()
+    }
+
+    fn get<'f>(fetch: &'f mut Self::Fetch<'_>, index: u32) -> Option<Self::Item<'f>> {
+        fetch.get_mut(index)
+    }
+}
+
+macro_rules! query_tuple_impl {
+    ($($name:ident),+) => {
+        impl<$($name: Query),+> Query for ($($name,)+) {
+            type Fetch<'w> = ($($name::Fetch<'w>,)+);
+            type Item<'f> = ($($name::Item<'f>,)+);
+
+            fn fetch(world: &World) -> Option<Self::Fetch<'_>> {
+                Some(($($name::fetch(world)?,)+))
+            }
+
+            #[allow(non_snake_case)]
+            fn get<'f>(fetch: &'f mut Self::Fetch<'_>, index: u32) -> Option<Self::Item<'f>> {
+                let ($($name,)+) = fetch;
+                Some(($($name::get($name, index)?,)+))
+            }
+        }
+    };
+}
+
+query_tuple_impl!(A);
+query_tuple_impl!(A, B);
+query_tuple_impl!(A, B, C);
+query_tuple_impl!(A, B, C, D);
+query_tuple_impl!(A, B, C, D, E);
+query_tuple_impl!(A, B, C, D, E, F);
+query_tuple_impl!(A, B, C, D, E, F, G);
+query_tuple_impl!(A, B, C, D, E, F, G, H);
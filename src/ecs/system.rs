@@ -1 +1,53 @@
-
+use crate::ecs::World;
+
+/// A unit of behavior that runs against the `ecs::World` once per tick.
+pub trait System {
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+
+    fn run(&mut self, world: &mut World, delta_time: f64);
+}
+
+impl<F: FnMut(&mut World, f64)> System for F {
+    fn run(&mut self, world: &mut World, delta_time: f64) {
+        self(world, delta_time)
+    }
+}
+
+/// Runs systems one after another, in the order they were added.
+#[derive(Default)]
+pub struct Schedule {
+    systems: Vec<Box<dyn System>>,
+}
+
+impl Schedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_system(&mut self, system: impl System + 'static) -> &mut Self {
+        self.systems.push(Box::new(system));
+        self
+    }
+
+    pub fn with_system(mut self, system: impl System + 'static) -> Self {
+        self.add_system(system);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.systems.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.systems.is_empty()
+    }
+
+    pub fn run(&mut self, world: &mut World, delta_time: f64) {
+        for system in &mut self.systems {
+            log::trace!("Running system {}", system.name());
+            system.run(world, delta_time);
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use std::{
+    any::{Any, TypeId},
+    cell::{Ref, RefCell, RefMut},
+    collections::HashMap,
+};
+
+use crate::ecs::{Component, Entity, EntityAllocator, Query, Storage};
+
+/// Entities and all of their components.
+///
+/// Each component type has its own storage behind a `RefCell`, so queries can
+/// read and write different component types at the same time. Borrowing the
+/// same component type mutably twice in one query panics.
+#[derive(Default)]
+pub struct World {
+    entities: EntityAllocator,
+    storages: HashMap<TypeId, RefCell<Box<dyn AnyStorage>>>,
+}
+
+impl World {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn spawn(&mut self) -> Entity {
+        self.entities.allocate()
+    }
+
+    /// Remove an entity and all of its components.
+    ///
+    /// Returns `false` if the entity was already dead.
+    pub fn despawn(&mut self, entity: Entity) -> bool {
+        if !self.entities.deallocate(entity) {
+            return false;
+        }
+
+        for storage in self.storages.values_mut() {
+            storage.get_mut().remove_index(entity.index());
+        }
+
+        true
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.entities.is_alive(entity)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    pub fn entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.entities.iter()
+    }
+
+    /// Attach a component to an entity, returning the component it replaced.
+    ///
+    /// Components can't be attached to dead entities, so `component` is
+    /// handed straight back in that case.
+    pub fn insert<T: Component>(&mut self, entity: Entity, component: T) -> Result<Option<T>, T> {
+        if !self.is_alive(entity) {
+            return Err(component);
+        }
+
+        let storage = self
+            .storages
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| RefCell::new(Box::new(ErasedStorage::<T>(T::Storage::default()))));
+
+        Ok(downcast_mut::<T>(storage.get_mut().as_mut()).insert(entity.index(), component))
+    }
+
+    pub fn remove<T: Component>(&mut self, entity: Entity) -> Option<T> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+
+        let storage = self.storages.get_mut(&TypeId::of::<T>())?;
+        downcast_mut::<T>(storage.get_mut().as_mut()).remove(entity.index())
+    }
+
+    pub fn get<T: Component>(&self, entity: Entity) -> Option<Ref<'_, T>> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+
+        let storage = self.storage::<T>()?;
+        Ref::filter_map(storage, |storage| storage.get(entity.index())).ok()
+    }
+
+    pub fn get_mut<T: Component>(&self, entity: Entity) -> Option<RefMut<'_, T>> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+
+        let storage = self.storage_mut::<T>()?;
+        RefMut::filter_map(storage, |storage| storage.get_mut(entity.index())).ok()
+    }
+
+    pub fn has<T: Component>(&self, entity: Entity) -> bool {
+        self.is_alive(entity)
+            && self
+                .storage::<T>()
+                .is_some_and(|storage| storage.contains(entity.index()))
+    }
+
+    /// Borrow the whole storage for a component type, if any have been inserted.
+    pub fn storage<T: Component>(&self) -> Option<Ref<'_, T::Storage>> {
+        let storage = self.storages.get(&TypeId::of::<T>())?;
+        Some(Ref::map(storage.borrow(), |storage| {
+            downcast_ref::<T>(storage.as_ref())
+        }))
+    }
+
+    pub fn storage_mut<T: Component>(&self) -> Option<RefMut<'_, T::Storage>> {
+        let storage = self.storages.get(&TypeId::of::<T>())?;
+        Some(RefMut::map(storage.borrow_mut(), |storage| {
+            downcast_mut::<T>(storage.as_mut())
+        }))
+    }
+
+    /// Call `f` for every live entity that has all of the components in `Q`.
+    ///
+    /// ```ignore
+    /// world.query::<(Read<Velocity>, Write<Position>)>(|_, (velocity, position)| {
+    ///     position.0 += &velocity.0;
+    /// });
+    /// ```
+    pub fn query<Q: Query>(&self, mut f: impl FnMut(Entity, Q::Item<'_>)) {
+        let mut fetch = match Q::fetch(self) {
+            Some(fetch) => fetch,
+            // A component type nothing has ever had can't match anything
+            None => return,
+        };
+
+        for entity in self.entities.iter() {
+            if let Some(item) = Q::get(&mut fetch, entity.index()) {
+                f(entity, item);
+            }
+        }
+    }
+}
+
+trait AnyStorage {
+    fn remove_index(&mut self, index: u32);
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+/// Storages are generic over their component type, so they're wrapped to get
+/// a single `AnyStorage` impl per component.
+struct ErasedStorage<T: Component>(T::Storage);
+
+impl<T: Component> AnyStorage for ErasedStorage<T> {
+    fn remove_index(&mut self, index: u32) {
+        self.0.remove(index);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[inline]
+fn downcast_ref<T: Component>(storage: &dyn AnyStorage) -> &T::Storage {
+    &storage
+        .as_any()
+        .downcast_ref::<ErasedStorage<T>>()
+        .expect("Storage registered under the wrong TypeId")
+        .0
+}
+
+#[inline]
+fn downcast_mut<T: Component>(storage: &mut dyn AnyStorage) -> &mut T::Storage {
+    &mut storage
+        .as_any_mut()
+        .downcast_mut::<ErasedStorage<T>>()
+        .expect("Storage registered under the wrong TypeId")
+        .0
+}
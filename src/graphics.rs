@@ -1,8 +1,10 @@
 pub mod camera;
 pub mod color;
-pub mod config;
 pub mod instance;
 pub mod object;
+pub mod pipeline;
+pub mod ray;
+pub mod render_target;
 pub mod shaders;
 pub mod shape;
 pub mod state;
@@ -11,10 +13,12 @@ pub mod vertex;
 
 pub use camera::{Camera, CameraController};
 pub use color::Color;
-pub use config::GraphicsConfig;
 pub use instance::{Instance, InstanceRaw};
-pub use object::Object;
-pub use shaders::ShaderCompiler;
+pub use object::{InstanceId, Object};
+pub use pipeline::{PipelineCache, PipelineKey};
+pub use ray::Ray;
+pub use render_target::RenderTarget;
+pub use shaders::{ShaderCompiler, ShaderProgram};
 pub use state::State;
 pub use uniforms::Uniforms;
 pub use vertex::Vertex;
@@ -1,5 +1,8 @@
 use winit::event::{ElementState, KeyboardInput, VirtualKeyCode, WindowEvent};
 
+use crate::common::config::CameraConfig;
+use crate::graphics::Ray;
+
 pub struct Camera {
     pub eye: cgmath::Point3<f32>,
     pub up: cgmath::Vector3<f32>,
@@ -10,12 +13,93 @@ pub struct Camera {
 }
 
 impl Camera {
+    pub fn new(config: &CameraConfig, aspect: f32) -> Self {
+        Self {
+            eye: (0.0, 1.0, 50.0).into(),
+            up: cgmath::Vector3::unit_y(),
+            aspect,
+            fovy: config.fovy as f32,
+            znear: config.znear as f32,
+            zfar: config.zfar as f32,
+        }
+    }
+
+    /// Take the projection settings from `config`, leaving the camera where it is.
+    ///
+    /// Like [`Camera::frame`], `zfar` is pushed out if needed so the z = 0
+    /// plane stays in view.
+    pub fn apply_config(&mut self, config: &CameraConfig) {
+        self.fovy = config.fovy as f32;
+        self.znear = config.znear as f32;
+        self.zfar = (config.zfar as f32).max(self.eye.z.abs() * 2.0);
+    }
+
     pub fn build_view_projection_matrix(&self) -> cgmath::Matrix4<f32> {
         let target = (self.eye.x, self.eye.y, self.eye.z - 1.0).into();
         let view = cgmath::Matrix4::look_at(self.eye, target, self.up);
         let proj = cgmath::perspective(cgmath::Deg(self.fovy), self.aspect, self.znear, self.zfar);
         return OPENGL_TO_WGPU_MATRIX * proj * view;
     }
+
+    /// Move the camera so that a `height` tall region of the z = 0 plane,
+    /// centered on `center`, fills the view vertically.
+    pub fn frame(&mut self, center: cgmath::Point2<f32>, height: f32) {
+        let distance = (height / 2.0) / (self.fovy.to_radians() / 2.0).tan();
+
+        self.eye = (center.x, center.y, distance).into();
+        self.zfar = self.zfar.max(distance * 2.0);
+    }
+
+    /// The ray from the camera through `screen`, in physical pixels from the
+    /// top left of a `width` by `height` window.
+    ///
+    /// Returns `None` if the window has no area or the projection can't be
+    /// inverted.
+    pub fn screen_to_world_ray(&self, screen: cgmath::Point2<f32>, width: u32, height: u32) -> Option<Ray> {
+        use cgmath::SquareMatrix;
+
+        if width == 0 || height == 0 {
+            return None;
+        }
+        // f32 is too imprecise to invert a projection with a far away zfar
+        let inverse = self.build_view_projection_matrix().cast::<f64>()?.invert()?;
+
+        let x = 2.0 * screen.x as f64 / width as f64 - 1.0;
+        let y = 1.0 - 2.0 * screen.y as f64 / height as f64;
+        // After `OPENGL_TO_WGPU_MATRIX` depth runs from 0 at znear to 1 at zfar
+        let unproject = |depth: f64| {
+            let point = inverse * cgmath::Vector4::new(x, y, depth, 1.0);
+            cgmath::Point3::new(point.x / point.w, point.y / point.w, point.z / point.w)
+        };
+        let near = unproject(0.0);
+        let far = unproject(1.0);
+
+        Some(Ray::new(near.cast()?, (far - near).cast()?))
+    }
+
+    /// Where `point` is drawn in a `width` by `height` window, in physical
+    /// pixels from the top left, or `None` if it is behind the camera.
+    ///
+    /// Points outside the view still get a position, off the edge of the window.
+    pub fn world_to_screen(&self, point: cgmath::Point3<f32>, width: u32, height: u32) -> Option<cgmath::Point2<f32>> {
+        let clip = self.build_view_projection_matrix() * point.to_homogeneous();
+        if clip.w <= 0.0 {
+            return None;
+        }
+
+        let x = clip.x / clip.w;
+        let y = clip.y / clip.w;
+        Some(cgmath::Point2::new(
+            (x + 1.0) / 2.0 * width as f32,
+            (1.0 - y) / 2.0 * height as f32,
+        ))
+    }
+
+    /// The point on the z = 0 plane under `screen`, where the world is drawn.
+    pub fn screen_to_world(&self, screen: cgmath::Point2<f32>, width: u32, height: u32) -> Option<cgmath::Point2<f32>> {
+        let point = self.screen_to_world_ray(screen, width, height)?.intersect_z_plane(0.0)?;
+        Some(cgmath::Point2::new(point.x, point.y))
+    }
 }
 
 #[rustfmt::skip]
@@ -45,6 +129,10 @@ impl CameraController {
         }
     }
 
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed;
+    }
+
     pub fn process_events(&mut self, event: &WindowEvent) -> bool {
         match event {
             WindowEvent::KeyboardInput {
@@ -1,14 +1,37 @@
-#[derive(Clone, Debug)]
+use serde::{Deserialize, Serialize};
+
+use crate::graphics::{color, Color};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Instance {
     pub position: cgmath::Vector3<f32>,
     pub rotation: cgmath::Quaternion<f32>,
+    pub scale: f32,
+    /// Multiplied with the object's vertex colors.
+    #[serde(default = "default_color")]
+    pub color: Color,
+}
+
+fn default_color() -> Color {
+    color::WHITE
 }
 
 impl Instance {
+    pub fn new(position: cgmath::Vector3<f32>, rotation: cgmath::Quaternion<f32>) -> Self {
+        Self {
+            position,
+            rotation,
+            scale: 1.0,
+            color: color::WHITE,
+        }
+    }
+
     pub fn to_raw(&self) -> InstanceRaw {
         InstanceRaw {
             model: cgmath::Matrix4::from_translation(self.position)
-                * cgmath::Matrix4::from(self.rotation),
+                * cgmath::Matrix4::from(self.rotation)
+                * cgmath::Matrix4::from_scale(self.scale),
+            color: [self.color[0], self.color[1], self.color[2], 1.0],
         }
     }
 }
@@ -17,6 +40,8 @@ impl Instance {
 #[derive(Copy, Clone, Debug)]
 pub struct InstanceRaw {
     pub model: cgmath::Matrix4<f32>,
+    /// A `vec4` rather than a `vec3` to match the shader's std430 layout.
+    pub color: [f32; 4],
 }
 
 unsafe impl bytemuck::Pod for InstanceRaw {}
@@ -1,12 +1,39 @@
-use crate::graphics::{Vertex, Instance};
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::graphics::{Vertex, Instance, InstanceRaw, Ray, Uniforms};
+
+/// Room for this many instances is allocated up front so that the instance
+/// buffer is never empty and small objects never need to grow.
+const INITIAL_INSTANCE_CAPACITY: usize = 16;
+
+/// A stable handle to one instance of an `Object`.
+///
+/// Handles are never reused, and stay valid until their instance is removed
+/// no matter how many other instances are added or removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct InstanceId(u64);
 
 pub struct Object {
     vertex_buffer: wgpu::Buffer,
     index_buffer: wgpu::Buffer,
     instance_buffer: wgpu::Buffer,
-    instances: Vec<Instance>,
+    bind_group: Option<wgpu::BindGroup>,
     num_indices: u32,
-    instance_buffer_size: usize,
+    /// How far the furthest vertex is from the origin.
+    radius: f32,
+
+    // Instances are packed densely so they can be drawn with a single call.
+    // Removing one moves the last instance into its slot.
+    instances: Vec<Instance>,
+    raw_instances: Vec<InstanceRaw>,
+    ids: Vec<InstanceId>,
+    slots: HashMap<InstanceId, usize>,
+    next_id: u64,
+
+    instance_capacity: usize,
+    dirty: Option<Range<usize>>,
+    reallocate: bool,
 }
 
 impl Object {
@@ -20,19 +47,31 @@ impl Object {
             wgpu::BufferUsage::INDEX,
         );
         let num_indices = indices.len() as u32;
+        let radius = vertices
+            .iter()
+            .map(|vertex| {
+                let [x, y, z] = vertex.position;
+                (x * x + y * y + z * z).sqrt()
+            })
+            .fold(0.0, f32::max);
 
-        let instance_buffer = device.create_buffer_with_data(
-            &[0, 1, 2],
-            wgpu::BufferUsage::STORAGE,
-        );
+        let instance_buffer = Self::create_instance_buffer(device, INITIAL_INSTANCE_CAPACITY);
 
         Self {
             vertex_buffer,
             index_buffer,
             instance_buffer,
-            instances: Vec::new(),
+            bind_group: None,
             num_indices,
-            instance_buffer_size: 0,
+            radius,
+            instances: Vec::new(),
+            raw_instances: Vec::new(),
+            ids: Vec::new(),
+            slots: HashMap::new(),
+            next_id: 0,
+            instance_capacity: INITIAL_INSTANCE_CAPACITY,
+            dirty: None,
+            reallocate: false,
         }
     }
 
@@ -48,27 +87,205 @@ impl Object {
         self.num_indices
     }
 
+    /// How far the furthest vertex is from the origin, before instances
+    /// scale it.
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
     pub fn instance_buffer(&self) -> &wgpu::Buffer {
         &self.instance_buffer
     }
 
+    /// The bind group holding the camera uniforms and this object's instances.
+    ///
+    /// `None` until [`Object::bind`] has been called.
+    pub fn bind_group(&self) -> Option<&wgpu::BindGroup> {
+        self.bind_group.as_ref()
+    }
+
+    /// Create the bind group for this object. Needs calling again whenever
+    /// [`Object::upload`] replaces the instance buffer.
+    pub fn bind(&mut self, device: &wgpu::Device, layout: &wgpu::BindGroupLayout, uniform_buffer: &wgpu::Buffer) {
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            bindings: &[
+                wgpu::Binding {
+                    binding: 0,
+                    resource: wgpu::BindingResource::Buffer {
+                        buffer: uniform_buffer,
+                        // FYI: you can share a single buffer between bindings.
+                        range: 0..std::mem::size_of::<Uniforms>() as wgpu::BufferAddress,
+                    },
+                },
+                wgpu::Binding {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Buffer {
+                        buffer: &self.instance_buffer,
+                        range: 0..self.instance_buffer_size() as wgpu::BufferAddress,
+                    },
+                },
+            ],
+            label: Some("object_bind_group"),
+        });
+
+        self.bind_group = Some(bind_group);
+    }
+
+    /// The size in bytes of the whole instance buffer, used or not.
     pub fn instance_buffer_size(&self) -> usize {
-        self.instance_buffer_size
+        self.instance_capacity * std::mem::size_of::<InstanceRaw>()
     }
 
     pub fn num_instances(&self) -> usize {
         self.instances.len()
     }
 
-    pub fn add_instance(&mut self, device: &wgpu::Device, position: cgmath::Vector3<f32>, rotation: cgmath::Quaternion<f32>) {
-        self.instances.push(Instance{ position, rotation });
+    pub fn instance(&self, id: InstanceId) -> Option<&Instance> {
+        self.slots.get(&id).map(|&slot| &self.instances[slot])
+    }
 
-        let instance_data = self.instances.iter().map(Instance::to_raw).collect::<Vec<_>>();
-        self.instance_buffer_size = instance_data.len() * std::mem::size_of::<cgmath::Matrix4<f32>>();
+    /// Every instance and its handle, in draw order.
+    pub fn instances(&self) -> impl Iterator<Item = (InstanceId, &Instance)> {
+        self.ids.iter().copied().zip(&self.instances)
+    }
 
-        self.instance_buffer = device.create_buffer_with_data(
-            bytemuck::cast_slice(&instance_data),
-            wgpu::BufferUsage::STORAGE_READ,
-        );
+    /// The instance `ray` hits first, treating each one as a disc as wide as
+    /// the object facing down the z axis. Where discs overlap at the same
+    /// depth the one drawn last wins.
+    pub fn pick(&self, ray: &Ray) -> Option<InstanceId> {
+        use cgmath::InnerSpace;
+
+        let mut nearest: Option<(f32, InstanceId)> = None;
+
+        for (id, instance) in self.instances() {
+            let distance = match ray.z_plane_distance(instance.position.z) {
+                Some(distance) => distance,
+                None => continue,
+            };
+            let point = ray.at(distance);
+            let offset = cgmath::Vector2::new(point.x - instance.position.x, point.y - instance.position.y);
+
+            let hit = offset.magnitude() <= self.radius * instance.scale;
+            if hit && nearest.map_or(true, |(nearest, _)| distance <= nearest) {
+                nearest = Some((distance, id));
+            }
+        }
+
+        nearest.map(|(_, id)| id)
+    }
+
+    pub fn add_instance(&mut self, instance: Instance) -> InstanceId {
+        let id = InstanceId(self.next_id);
+        self.next_id += 1;
+
+        let slot = self.instances.len();
+        self.raw_instances.push(instance.to_raw());
+        self.instances.push(instance);
+        self.ids.push(id);
+        self.slots.insert(id, slot);
+
+        self.mark_dirty(slot);
+        id
+    }
+
+    /// Returns `false` if the instance doesn't exist.
+    pub fn update_instance(&mut self, id: InstanceId, instance: Instance) -> bool {
+        match self.slots.get(&id) {
+            Some(&slot) => {
+                self.raw_instances[slot] = instance.to_raw();
+                self.instances[slot] = instance;
+                self.mark_dirty(slot);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn remove_instance(&mut self, id: InstanceId) -> Option<Instance> {
+        let slot = self.slots.remove(&id)?;
+
+        let removed = self.instances.swap_remove(slot);
+        self.raw_instances.swap_remove(slot);
+        self.ids.swap_remove(slot);
+
+        // The last instance was moved into the hole, so its slot needs fixing up and re-uploading
+        if let Some(&moved) = self.ids.get(slot) {
+            self.slots.insert(moved, slot);
+            self.mark_dirty(slot);
+        }
+
+        Some(removed)
+    }
+
+    /// Replace every instance, invalidating all existing handles.
+    pub fn set_instances(&mut self, instances: Vec<Instance>) -> Vec<InstanceId> {
+        self.instances.clear();
+        self.raw_instances.clear();
+        self.ids.clear();
+        self.slots.clear();
+
+        let ids = instances
+            .into_iter()
+            .map(|instance| self.add_instance(instance))
+            .collect();
+
+        if self.instances.is_empty() {
+            self.dirty = None;
+        }
+
+        ids
+    }
+
+    /// Upload any instances changed since the last upload.
+    ///
+    /// Only the changed range is written, unless the buffer had to grow, in
+    /// which case a new buffer is created and `true` is returned so the caller
+    /// can rebuild anything bound to the old one.
+    pub fn upload(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) -> bool {
+        let reallocated = if self.reallocate {
+            self.instance_buffer = Self::create_instance_buffer(device, self.instance_capacity);
+            self.dirty = Some(0..self.raw_instances.len());
+            self.reallocate = false;
+            true
+        } else {
+            false
+        };
+
+        if let Some(dirty) = self.dirty.take() {
+            let end = dirty.end.min(self.raw_instances.len());
+            if dirty.start < end {
+                let offset = dirty.start * std::mem::size_of::<InstanceRaw>();
+                queue.write_buffer(
+                    &self.instance_buffer,
+                    offset as wgpu::BufferAddress,
+                    bytemuck::cast_slice(&self.raw_instances[dirty.start..end]),
+                );
+            }
+        }
+
+        reallocated
+    }
+
+    fn mark_dirty(&mut self, slot: usize) {
+        if slot >= self.instance_capacity {
+            while slot >= self.instance_capacity {
+                self.instance_capacity *= 2;
+            }
+            self.reallocate = true;
+        }
+
+        self.dirty = Some(match self.dirty.take() {
+            Some(dirty) => dirty.start.min(slot)..dirty.end.max(slot + 1),
+            None => slot..slot + 1,
+        });
+    }
+
+    fn create_instance_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("instance_buffer"),
+            size: (capacity * std::mem::size_of::<InstanceRaw>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsage::STORAGE_READ | wgpu::BufferUsage::COPY_DST,
+        })
     }
 }
@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+
+use crate::graphics::{ShaderCompiler, ShaderProgram, Vertex};
+
+/// Everything that distinguishes one render pipeline from another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PipelineKey {
+    pub shader: ShaderProgram,
+    pub format: wgpu::TextureFormat,
+    pub topology: wgpu::PrimitiveTopology,
+    pub cull_mode: wgpu::CullMode,
+}
+
+impl PipelineKey {
+    /// The pipeline used to draw every `Object` today.
+    pub fn basic(format: wgpu::TextureFormat) -> Self {
+        Self {
+            shader: ShaderProgram::Basic,
+            format,
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            cull_mode: wgpu::CullMode::Back,
+        }
+    }
+}
+
+/// Builds render pipelines on first use and hands back the same one after that.
+///
+/// Compiled shader modules are cached separately, so pipelines that only
+/// differ in render state share a single compile.
+pub struct PipelineCache {
+    compiler: ShaderCompiler,
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline_layout: wgpu::PipelineLayout,
+    modules: HashMap<ShaderProgram, (wgpu::ShaderModule, wgpu::ShaderModule)>,
+    pipelines: HashMap<PipelineKey, wgpu::RenderPipeline>,
+}
+
+impl PipelineCache {
+    pub fn new(device: &wgpu::Device) -> anyhow::Result<Self> {
+        let bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                bindings: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStage::VERTEX,
+                        ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+                    },
+                    // Each object binds its own instance buffer here
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStage::VERTEX,
+                        ty: wgpu::BindingType::StorageBuffer {
+                            // Buffers are swapped out rather than resized when they grow
+                            dynamic: false,
+                            // The shader is not allowed to modify it's contents
+                            readonly: true,
+                        },
+                    },
+                ],
+                label: Some("uniform_bind_group_layout"),
+            });
+
+        let pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                bind_group_layouts: &[&bind_group_layout],
+            });
+
+        Ok(Self {
+            compiler: ShaderCompiler::new()?,
+            bind_group_layout,
+            pipeline_layout,
+            modules: HashMap::new(),
+            pipelines: HashMap::new(),
+        })
+    }
+
+    /// The layout every `Object` bind group must match.
+    pub fn bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.bind_group_layout
+    }
+
+    /// A pipeline that has already been built.
+    pub fn get(&self, key: &PipelineKey) -> Option<&wgpu::RenderPipeline> {
+        self.pipelines.get(key)
+    }
+
+    pub fn get_or_create(&mut self, device: &wgpu::Device, key: PipelineKey) -> anyhow::Result<&wgpu::RenderPipeline> {
+        if !self.pipelines.contains_key(&key) {
+            let pipeline = self.create_pipeline(device, key)?;
+            self.pipelines.insert(key, pipeline);
+        }
+
+        Ok(&self.pipelines[&key])
+    }
+
+    pub fn len(&self) -> usize {
+        self.pipelines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pipelines.is_empty()
+    }
+
+    /// Load shaders from disk from now on and watch them for changes, see
+    /// [`PipelineCache::reload_changed_shaders`].
+    ///
+    /// Pipelines that were already built keep their embedded shaders until
+    /// their files change.
+    pub fn enable_hot_reload(&mut self) -> anyhow::Result<()> {
+        self.compiler.enable_hot_reload()
+    }
+
+    /// Stop watching shaders. Existing pipelines keep whatever they were last built with.
+    pub fn disable_hot_reload(&mut self) {
+        self.compiler.disable_hot_reload();
+    }
+
+    pub fn hot_reload_enabled(&self) -> bool {
+        self.compiler.hot_reload_enabled()
+    }
+
+    /// Recompile any shader programs whose files changed on disk and rebuild
+    /// the pipelines that use them.
+    ///
+    /// Compile errors are logged and the last working pipeline is kept, so a
+    /// typo in a shader never takes down the app. Returns the programs that
+    /// were reloaded.
+    pub fn reload_changed_shaders(&mut self, device: &wgpu::Device) -> Vec<ShaderProgram> {
+        let changed = self.compiler.changed_files();
+        if changed.is_empty() {
+            return Vec::new();
+        }
+
+        let mut reloaded = Vec::new();
+        for &program in ShaderProgram::ALL.iter().filter(|program| program.uses_any(&changed)) {
+            let modules = match self.compile(device, program) {
+                Ok(modules) => modules,
+                Err(error) => {
+                    log::error!("Keeping the last good {:?} shader: {:#}", program, error);
+                    continue;
+                }
+            };
+
+            self.modules.insert(program, modules);
+
+            let keys: Vec<PipelineKey> = self
+                .pipelines
+                .keys()
+                .filter(|key| key.shader == program)
+                .copied()
+                .collect();
+
+            for key in keys {
+                let pipeline = self.build_pipeline(device, key);
+                self.pipelines.insert(key, pipeline);
+            }
+
+            log::info!("Reloaded {:?} shader", program);
+            reloaded.push(program);
+        }
+
+        reloaded
+    }
+
+    fn compile(&mut self, device: &wgpu::Device, program: ShaderProgram) -> anyhow::Result<(wgpu::ShaderModule, wgpu::ShaderModule)> {
+        log::debug!("Compiling shader {:?}", program);
+        let vs_module = program.vertex_module(device, &mut self.compiler)?;
+        let fs_module = program.fragment_module(device, &mut self.compiler)?;
+
+        Ok((vs_module, fs_module))
+    }
+
+    fn create_pipeline(&mut self, device: &wgpu::Device, key: PipelineKey) -> anyhow::Result<wgpu::RenderPipeline> {
+        if !self.modules.contains_key(&key.shader) {
+            let modules = match self.compile(device, key.shader) {
+                Ok(modules) => modules,
+                // Like a failed reload, a broken shader on disk shouldn't stop
+                // the app when the embedded one still works
+                Err(error) if self.compiler.hot_reload_enabled() => {
+                    log::error!("Using the embedded {:?} shader: {:#}", key.shader, error);
+                    let program = key.shader;
+                    self.compiler.with_embedded_sources(|compiler| -> anyhow::Result<_> {
+                        Ok((program.vertex_module(device, compiler)?, program.fragment_module(device, compiler)?))
+                    })?
+                }
+                Err(error) => return Err(error),
+            };
+            self.modules.insert(key.shader, modules);
+        }
+
+        Ok(self.build_pipeline(device, key))
+    }
+
+    /// Shader modules for `key` must already be compiled.
+    fn build_pipeline(&self, device: &wgpu::Device, key: PipelineKey) -> wgpu::RenderPipeline {
+        let (vs_module, fs_module) = &self.modules[&key.shader];
+
+        log::debug!("Building render pipeline {:?}", key);
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            layout: &self.pipeline_layout,
+            vertex_stage: wgpu::ProgrammableStageDescriptor {
+                module: vs_module,
+                entry_point: "main",
+            },
+            fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
+                module: fs_module,
+                entry_point: "main",
+            }),
+            rasterization_state: Some(wgpu::RasterizationStateDescriptor {
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: key.cull_mode,
+                depth_bias: 0,
+                depth_bias_slope_scale: 0.0,
+                depth_bias_clamp: 0.0,
+            }),
+            primitive_topology: key.topology,
+            color_states: &[wgpu::ColorStateDescriptor {
+                format: key.format,
+                color_blend: wgpu::BlendDescriptor::REPLACE,
+                alpha_blend: wgpu::BlendDescriptor::REPLACE,
+                write_mask: wgpu::ColorWrite::ALL,
+            }],
+            depth_stencil_state: None,
+            vertex_state: wgpu::VertexStateDescriptor {
+                index_format: wgpu::IndexFormat::Uint16,
+                vertex_buffers: &[Vertex::descriptor()],
+            },
+            sample_count: 1,
+            sample_mask: !0,
+            alpha_to_coverage_enabled: false,
+        })
+    }
+}
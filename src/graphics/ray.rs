@@ -0,0 +1,43 @@
+use cgmath::InnerSpace;
+
+/// A half line starting at `origin`, such as the one from the camera through
+/// a point on screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: cgmath::Point3<f32>,
+    /// Always normalized.
+    pub direction: cgmath::Vector3<f32>,
+}
+
+impl Ray {
+    pub fn new(origin: cgmath::Point3<f32>, direction: cgmath::Vector3<f32>) -> Self {
+        Self {
+            origin,
+            direction: direction.normalize(),
+        }
+    }
+
+    pub fn at(&self, distance: f32) -> cgmath::Point3<f32> {
+        self.origin + self.direction * distance
+    }
+
+    /// How far along the ray it crosses the plane where every point has the
+    /// given `z`, or `None` if it runs parallel to the plane or away from it.
+    pub fn z_plane_distance(&self, z: f32) -> Option<f32> {
+        if self.direction.z == 0.0 {
+            return None;
+        }
+
+        let distance = (z - self.origin.z) / self.direction.z;
+        if distance >= 0.0 && distance.is_finite() {
+            Some(distance)
+        } else {
+            None
+        }
+    }
+
+    /// Where the ray crosses the plane where every point has the given `z`.
+    pub fn intersect_z_plane(&self, z: f32) -> Option<cgmath::Point3<f32>> {
+        self.z_plane_distance(z).map(|distance| self.at(distance))
+    }
+}
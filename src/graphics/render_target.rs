@@ -0,0 +1,213 @@
+use anyhow::Context;
+
+use crate::common::config::PresentMode;
+
+/// The texture format used for offscreen rendering, chosen so that captured
+/// frames can be copied straight into an `image::RgbaImage`.
+pub const OFFSCREEN_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+const BYTES_PER_PIXEL: u32 = 4;
+
+/// Texture to buffer copies need every row to start on this many bytes.
+const COPY_BYTES_PER_ROW_ALIGNMENT: u32 = 256;
+
+/// Where `graphics::State` draws each frame.
+pub enum RenderTarget {
+    Window {
+        surface: wgpu::Surface,
+        sc_desc: wgpu::SwapChainDescriptor,
+        swap_chain: wgpu::SwapChain,
+    },
+    Offscreen {
+        texture: wgpu::Texture,
+        view: wgpu::TextureView,
+        output_buffer: wgpu::Buffer,
+        width: u32,
+        height: u32,
+    },
+}
+
+impl RenderTarget {
+    pub fn window(device: &wgpu::Device, surface: wgpu::Surface, width: u32, height: u32, present_mode: PresentMode) -> Self {
+        let sc_desc = wgpu::SwapChainDescriptor {
+            usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+            format: wgpu::TextureFormat::Bgra8UnormSrgb,
+            width,
+            height,
+            present_mode: present_mode.into(),
+        };
+        let swap_chain = device.create_swap_chain(&surface, &sc_desc);
+
+        RenderTarget::Window {
+            surface,
+            sc_desc,
+            swap_chain,
+        }
+    }
+
+    pub fn offscreen(device: &wgpu::Device, width: u32, height: u32) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth: 1,
+            },
+            array_layer_count: 1,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: OFFSCREEN_FORMAT,
+            usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT | wgpu::TextureUsage::COPY_SRC,
+            label: Some("offscreen_texture"),
+        });
+        let view = texture.create_default_view();
+
+        let output_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            size: (padded_bytes_per_row(width) * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsage::COPY_DST | wgpu::BufferUsage::MAP_READ,
+            label: Some("offscreen_output_buffer"),
+        });
+
+        RenderTarget::Offscreen {
+            texture,
+            view,
+            output_buffer,
+            width,
+            height,
+        }
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        match self {
+            RenderTarget::Window { sc_desc, .. } => sc_desc.format,
+            RenderTarget::Offscreen { .. } => OFFSCREEN_FORMAT,
+        }
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        match self {
+            RenderTarget::Window { sc_desc, .. } => (sc_desc.width, sc_desc.height),
+            RenderTarget::Offscreen { width, height, .. } => (*width, *height),
+        }
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        match self {
+            RenderTarget::Window {
+                surface,
+                sc_desc,
+                swap_chain,
+            } => {
+                sc_desc.width = width;
+                sc_desc.height = height;
+                *swap_chain = device.create_swap_chain(surface, sc_desc);
+            }
+            RenderTarget::Offscreen { .. } => {
+                *self = RenderTarget::offscreen(device, width, height);
+            }
+        }
+    }
+
+    /// Recreate the swap chain if `present_mode` differs from the current one.
+    ///
+    /// Does nothing when rendering offscreen.
+    pub fn set_present_mode(&mut self, device: &wgpu::Device, present_mode: PresentMode) {
+        if let RenderTarget::Window {
+            surface,
+            sc_desc,
+            swap_chain,
+        } = self
+        {
+            let present_mode = present_mode.into();
+            if sc_desc.present_mode != present_mode {
+                sc_desc.present_mode = present_mode;
+                *swap_chain = device.create_swap_chain(surface, sc_desc);
+            }
+        }
+    }
+
+    /// Queue a copy of the offscreen texture into the readback buffer.
+    ///
+    /// Does nothing when rendering to a window.
+    pub fn copy_to_output(&self, encoder: &mut wgpu::CommandEncoder) {
+        if let RenderTarget::Offscreen {
+            texture,
+            output_buffer,
+            width,
+            height,
+            ..
+        } = self
+        {
+            encoder.copy_texture_to_buffer(
+                wgpu::TextureCopyView {
+                    texture,
+                    mip_level: 0,
+                    array_layer: 0,
+                    origin: wgpu::Origin3d::ZERO,
+                },
+                wgpu::BufferCopyView {
+                    buffer: output_buffer,
+                    offset: 0,
+                    bytes_per_row: padded_bytes_per_row(*width),
+                    rows_per_image: *height,
+                },
+                wgpu::Extent3d {
+                    width: *width,
+                    height: *height,
+                    depth: 1,
+                },
+            );
+        }
+    }
+
+    /// Read the last frame copied by `copy_to_output` back from the GPU.
+    pub async fn read_output(&self, device: &wgpu::Device) -> anyhow::Result<image::RgbaImage> {
+        match self {
+            RenderTarget::Window { .. } => {
+                anyhow::bail!("Frames can only be captured from an offscreen render target")
+            }
+            RenderTarget::Offscreen {
+                output_buffer,
+                width,
+                height,
+                ..
+            } => {
+                let padded_row = padded_bytes_per_row(*width) as usize;
+                let unpadded_row = (*width * BYTES_PER_PIXEL) as usize;
+                let buffer_size = (padded_row * *height as usize) as wgpu::BufferAddress;
+
+                let mapping_future = output_buffer.map_read(0, buffer_size);
+                device.poll(wgpu::Maintain::Wait);
+                let mapping = mapping_future
+                    .await
+                    .map_err(|_| anyhow::anyhow!("Failed to map the offscreen output buffer"))?;
+
+                // Rows are padded out to wgpu's copy alignment, strip that back off
+                let mut pixels = Vec::with_capacity(unpadded_row * *height as usize);
+                for row in mapping.as_slice().chunks(padded_row) {
+                    pixels.extend_from_slice(&row[..unpadded_row]);
+                }
+
+                image::RgbaImage::from_raw(*width, *height, pixels)
+                    .context("Captured frame has the wrong size")
+            }
+        }
+    }
+}
+
+impl From<PresentMode> for wgpu::PresentMode {
+    fn from(present_mode: PresentMode) -> Self {
+        match present_mode {
+            PresentMode::Immediate => wgpu::PresentMode::Immediate,
+            PresentMode::Mailbox => wgpu::PresentMode::Mailbox,
+            PresentMode::Fifo => wgpu::PresentMode::Fifo,
+        }
+    }
+}
+
+#[inline]
+fn padded_bytes_per_row(width: u32) -> u32 {
+    let unpadded = width * BYTES_PER_PIXEL;
+    let align = COPY_BYTES_PER_ROW_ALIGNMENT;
+    ((unpadded + align - 1) / align) * align
+}
@@ -1,17 +1,136 @@
 pub mod basic;
 
 use anyhow::Context;
-use wgpu::ShaderModuleSource;
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+};
+use wgpu::{Device, ShaderModule, ShaderModuleSource};
+
+use crate::common::watch::FileWatcher;
+
+/// Where the shader sources live in the source tree, for hot reloading.
+pub const SHADER_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/graphics/shaders");
+
+/// A GLSL source file, embedded at build time and optionally reloaded from disk.
+#[derive(Debug, Clone, Copy)]
+pub struct ShaderFile {
+    pub name: &'static str,
+    pub embedded: &'static str,
+}
+
+impl ShaderFile {
+    pub fn path(&self) -> PathBuf {
+        Path::new(SHADER_DIR).join(self.name)
+    }
+}
+
+/// Every shader program the renderer knows how to build.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShaderProgram {
+    Basic,
+}
+
+impl ShaderProgram {
+    pub const ALL: &'static [ShaderProgram] = &[ShaderProgram::Basic];
+
+    pub fn files(self) -> &'static [ShaderFile] {
+        match self {
+            ShaderProgram::Basic => &[basic::VERTEX, basic::FRAGMENT],
+        }
+    }
+
+    /// Whether any of this program's source files is in `paths`.
+    pub fn uses_any(self, paths: &[PathBuf]) -> bool {
+        self.files().iter().any(|file| {
+            paths
+                .iter()
+                .any(|path| path.file_name().map_or(false, |name| name == file.name))
+        })
+    }
+
+    pub fn vertex_module(self, device: &Device, compiler: &mut ShaderCompiler) -> anyhow::Result<ShaderModule> {
+        match self {
+            ShaderProgram::Basic => basic::vertex_module(device, compiler),
+        }
+    }
+
+    pub fn fragment_module(self, device: &Device, compiler: &mut ShaderCompiler) -> anyhow::Result<ShaderModule> {
+        match self {
+            ShaderProgram::Basic => basic::fragment_module(device, compiler),
+        }
+    }
+}
 
 pub struct ShaderCompiler {
     compiler: shaderc::Compiler,
+    watcher: Option<FileWatcher>,
 }
 
 impl ShaderCompiler {
     pub fn new() -> anyhow::Result<Self> {
         let compiler = shaderc::Compiler::new().context("Failed to create shader compiler")?;
 
-        Ok(Self { compiler })
+        Ok(Self {
+            compiler,
+            watcher: None,
+        })
+    }
+
+    /// Read shader sources from [`SHADER_DIR`] instead of the copies embedded
+    /// at build time, and watch them for changes.
+    pub fn enable_hot_reload(&mut self) -> anyhow::Result<()> {
+        let mut watcher = FileWatcher::new()?;
+        watcher.watch(SHADER_DIR)?;
+        self.watcher = Some(watcher);
+
+        log::info!("Watching {} for shader changes", SHADER_DIR);
+        Ok(())
+    }
+
+    /// Stop watching for changes and go back to the embedded shader sources.
+    pub fn disable_hot_reload(&mut self) {
+        self.watcher = None;
+    }
+
+    pub fn hot_reload_enabled(&self) -> bool {
+        self.watcher.is_some()
+    }
+
+    /// Run `f` with [`ShaderCompiler::load_source`] returning the embedded
+    /// sources, even if hot reloading is enabled. Changes on disk are still
+    /// picked up afterwards.
+    pub fn with_embedded_sources<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        let watcher = self.watcher.take();
+        let result = f(self);
+        self.watcher = watcher;
+
+        result
+    }
+
+    /// Shader files changed on disk since the last call. Always empty unless
+    /// hot reloading is enabled.
+    pub fn changed_files(&self) -> Vec<PathBuf> {
+        match &self.watcher {
+            Some(watcher) => watcher.changed_paths(),
+            None => Vec::new(),
+        }
+    }
+
+    /// The source for `file`, read from disk when hot reloading.
+    ///
+    /// Also returns the name to report compile errors against: the full path
+    /// when read from disk, so errors point at the file being edited.
+    pub fn load_source(&self, file: &ShaderFile) -> anyhow::Result<(Cow<'static, str>, String)> {
+        if !self.hot_reload_enabled() {
+            return Ok((Cow::Borrowed(file.embedded), file.name.to_string()));
+        }
+
+        let path = file.path();
+        let source = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+
+        Ok((Cow::Owned(source), path.display().to_string()))
     }
 
     pub fn create_fragment_shader(
@@ -48,13 +167,26 @@ impl ShaderCompiler {
         entry_point: impl AsRef<str>,
         kind: shaderc::ShaderKind,
     ) -> anyhow::Result<ShaderModuleSource> {
-        let spirv = self.compiler.compile_into_spirv(
-            source.as_ref(),
-            kind,
-            name.as_ref(),
-            entry_point.as_ref(),
-            None,
-        )?;
+        // shaderc reports errors as `name:line: error: ...`, one per line
+        let spirv = self
+            .compiler
+            .compile_into_spirv(
+                source.as_ref(),
+                kind,
+                name.as_ref(),
+                entry_point.as_ref(),
+                None,
+            )
+            .map_err(|error| match error {
+                shaderc::Error::CompilationError(count, messages) => anyhow::anyhow!(
+                    "{} error(s) compiling {}:\n{}",
+                    count,
+                    name.as_ref(),
+                    messages.trim_end()
+                ),
+                other => anyhow::Error::new(other)
+                    .context(format!("Failed to compile {}", name.as_ref())),
+            })?;
         let data = wgpu::util::make_spirv(spirv.as_binary_u8());
 
         Ok(data)
@@ -1,13 +1,23 @@
 use wgpu::{Device, ShaderModule};
 
-use super::ShaderCompiler;
+use super::{ShaderCompiler, ShaderFile};
+
+pub const VERTEX: ShaderFile = ShaderFile {
+    name: "basic.vert",
+    embedded: include_str!("basic.vert"),
+};
+
+pub const FRAGMENT: ShaderFile = ShaderFile {
+    name: "basic.frag",
+    embedded: include_str!("basic.frag"),
+};
 
 pub fn fragment_module(
     device: &Device,
     compiler: &mut ShaderCompiler,
 ) -> anyhow::Result<ShaderModule> {
-    let fs_src = include_str!("basic.frag");
-    let fs_data = compiler.create_fragment_shader(fs_src, "basic.frag", "main")?;
+    let (fs_src, fs_name) = compiler.load_source(&FRAGMENT)?;
+    let fs_data = compiler.create_fragment_shader(fs_src, fs_name, "main")?;
     Ok(device.create_shader_module(fs_data))
 }
 
@@ -15,7 +25,7 @@ pub fn vertex_module(
     device: &Device,
     compiler: &mut ShaderCompiler,
 ) -> anyhow::Result<ShaderModule> {
-    let vs_src = include_str!("basic.vert");
-    let vs_data = compiler.create_vertex_shader(vs_src, "basic.vert", "main")?;
+    let (vs_src, vs_name) = compiler.load_source(&VERTEX)?;
+    let vs_data = compiler.create_vertex_shader(vs_src, vs_name, "main")?;
     Ok(device.create_shader_module(vs_data))
 }
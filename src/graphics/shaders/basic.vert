@@ -10,12 +10,19 @@ uniform Uniforms {
     mat4 u_view_proj;
 };
 
+struct Instance {
+    mat4 model;
+    vec4 color;
+};
+
 layout(set=0, binding=1) 
 buffer Instances {
-    mat4 s_models[];
+    Instance s_instances[];
 };
 
 void main() {
-    v_color = a_color;
-    gl_Position = u_view_proj * s_models[gl_InstanceIndex] * vec4(a_position, 1.0);
+    Instance instance = s_instances[gl_InstanceIndex];
+
+    v_color = a_color * instance.color.rgb;
+    gl_Position = u_view_proj * instance.model * vec4(a_position, 1.0);
 }
@@ -1,7 +1,9 @@
+use anyhow::Context;
 use winit::{event::WindowEvent, window::Window};
 
+use crate::common::config::{CameraConfig, GraphicsConfig};
 use crate::graphics::{
-    shaders, Camera, CameraController, GraphicsConfig, Uniforms, Vertex, Object,
+    Camera, CameraController, Instance, InstanceId, PipelineCache, PipelineKey, RenderTarget, Uniforms, Vertex, Object,
 };
 
 pub struct State {
@@ -15,32 +17,47 @@ pub struct State {
 }
 
 struct GpuState {
-    surface: wgpu::Surface,
     device: wgpu::Device,
     queue: wgpu::Queue,
-    sc_desc: wgpu::SwapChainDescriptor,
-    swap_chain: wgpu::SwapChain,
-    render_pipeline: wgpu::RenderPipeline,
+    target: RenderTarget,
+    pipelines: PipelineCache,
+    pipeline_key: PipelineKey,
 
     uniform_buffer: wgpu::Buffer,
-    uniform_bind_group: wgpu::BindGroup,
 }
 
 impl State {
-    pub async fn new(window: &Window, config: GraphicsConfig) -> anyhow::Result<Self> {
+    pub async fn new(window: &Window, config: GraphicsConfig, camera: &CameraConfig) -> anyhow::Result<Self> {
         let size = window.inner_size();
 
         let surface = wgpu::Surface::create(window);
+        let (device, queue) = Self::request_device(Some(&surface)).await?;
+        let target = RenderTarget::window(&device, surface, size.width, size.height, config.present_mode);
 
+        Self::with_target(device, queue, target, config, camera)
+    }
+
+    /// Create a `State` that renders into an offscreen texture instead of a
+    /// window, for screenshots and running without a display.
+    ///
+    /// Use [`State::capture_frame`] to read back what was rendered.
+    pub async fn new_offscreen(width: u32, height: u32, config: GraphicsConfig, camera: &CameraConfig) -> anyhow::Result<Self> {
+        let (device, queue) = Self::request_device(None).await?;
+        let target = RenderTarget::offscreen(&device, width, height);
+
+        Self::with_target(device, queue, target, config, camera)
+    }
+
+    async fn request_device(compatible_surface: Option<&wgpu::Surface>) -> anyhow::Result<(wgpu::Device, wgpu::Queue)> {
         let adapter = wgpu::Adapter::request(
             &wgpu::RequestAdapterOptions {
                 power_preference: wgpu::PowerPreference::Default,
-                compatible_surface: Some(&surface),
+                compatible_surface,
             },
             wgpu::BackendBit::PRIMARY, // Vulkan + Metal + DX12 + Browser WebGPU
         )
         .await
-        .unwrap();
+        .context("Failed to find a suitable graphics adapter")?;
 
         let (device, queue) = adapter
             .request_device(&wgpu::DeviceDescriptor {
@@ -51,121 +68,38 @@ impl State {
             })
             .await;
 
-        let sc_desc = wgpu::SwapChainDescriptor {
-            usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
-            format: wgpu::TextureFormat::Bgra8UnormSrgb,
-            width: size.width,
-            height: size.height,
-            present_mode: wgpu::PresentMode::Fifo,
-        };
-        let swap_chain = device.create_swap_chain(&surface, &sc_desc);
-
-        let camera = Camera {
-            eye: (0.0, 1.0, 50.0).into(),
-            up: cgmath::Vector3::unit_y(),
-            aspect: sc_desc.width as f32 / sc_desc.height as f32,
-            fovy: 45.0,
-            znear: 0.1,
-            zfar: 100.0,
-        };
-
-        let camera_controller = CameraController::new(0.2);
+        Ok((device, queue))
+    }
+
+    fn with_target(
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        target: RenderTarget,
+        config: GraphicsConfig,
+        camera_config: &CameraConfig,
+    ) -> anyhow::Result<Self> {
+        let (width, height) = target.size();
+        let size = winit::dpi::PhysicalSize::new(width, height);
+
+        let camera = Camera::new(camera_config, width as f32 / height as f32);
+        let camera_controller = CameraController::new(camera_config.speed as f32);
 
         let mut uniforms = Uniforms::new();
         uniforms.update_view_proj(&camera);
 
-        let instance_buffer = device.create_buffer_with_data(&[0, 1, 2],  wgpu::BufferUsage::STORAGE_READ);
-
         let uniform_buffer = device.create_buffer_with_data(
             bytemuck::cast_slice(&[uniforms]),
             wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
         );
 
-        let uniform_bind_group_layout =
-            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                bindings: &[
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 0,
-                        visibility: wgpu::ShaderStage::VERTEX,
-                        ty: wgpu::BindingType::UniformBuffer { dynamic: false },
-                    },
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 1,
-                        visibility: wgpu::ShaderStage::VERTEX,
-                        ty: wgpu::BindingType::StorageBuffer {
-                            // We don't plan on changing the size of this buffer
-                            dynamic: false,
-                            // The shader is not allowed to modify it's contents
-                            readonly: true,
-                        },
-                    },
-                ],
-                label: Some("uniform_bind_group_layout"),
-            });
-
-        let uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &uniform_bind_group_layout,
-            bindings: &[
-                wgpu::Binding {
-                    binding: 0,
-                    resource: wgpu::BindingResource::Buffer {
-                        buffer: &uniform_buffer,
-                        range: 0..std::mem::size_of_val(&uniforms) as wgpu::BufferAddress,
-                    },
-                },
-                wgpu::Binding {
-                    binding: 1,
-                    resource: wgpu::BindingResource::Buffer {
-                        buffer: &instance_buffer,
-                        range: 0..1 as wgpu::BufferAddress,
-                    },
-                },
-            ],
-            label: Some("uniform_bind_group"),
-        });
-
-        let mut compiler = shaders::ShaderCompiler::new()?;
-        let vs_module = shaders::basic::vertex_module(&device, &mut compiler)?;
-        let fs_module = shaders::basic::fragment_module(&device, &mut compiler)?;
-
-        let render_pipeline_layout =
-            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                bind_group_layouts: &[&uniform_bind_group_layout],
-            });
-
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            layout: &render_pipeline_layout,
-            vertex_stage: wgpu::ProgrammableStageDescriptor {
-                module: &vs_module,
-                entry_point: "main",
-            },
-            fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
-                module: &fs_module,
-                entry_point: "main",
-            }),
-            rasterization_state: Some(wgpu::RasterizationStateDescriptor {
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: wgpu::CullMode::Back,
-                depth_bias: 0,
-                depth_bias_slope_scale: 0.0,
-                depth_bias_clamp: 0.0,
-            }),
-            primitive_topology: wgpu::PrimitiveTopology::TriangleList,
-            color_states: &[wgpu::ColorStateDescriptor {
-                format: sc_desc.format,
-                color_blend: wgpu::BlendDescriptor::REPLACE,
-                alpha_blend: wgpu::BlendDescriptor::REPLACE,
-                write_mask: wgpu::ColorWrite::ALL,
-            }],
-            depth_stencil_state: None,
-            vertex_state: wgpu::VertexStateDescriptor {
-                index_format: wgpu::IndexFormat::Uint16,
-                vertex_buffers: &[Vertex::descriptor()],
-            },
-            sample_count: 1,
-            sample_mask: !0,
-            alpha_to_coverage_enabled: false,
-        });
+        let mut pipelines = PipelineCache::new(&device)?;
+        if config.hot_reload_shaders {
+            if let Err(error) = pipelines.enable_hot_reload() {
+                log::warn!("Shader hot reloading is unavailable: {:#}", error);
+            }
+        }
+        let pipeline_key = PipelineKey::basic(target.format());
+        pipelines.get_or_create(&device, pipeline_key)?;
 
         Ok(Self {
             config,
@@ -175,14 +109,12 @@ impl State {
             size,
             objects: Vec::new(),
             gpu: GpuState {
-                surface,
                 device,
                 queue,
-                sc_desc,
-                swap_chain,
-                render_pipeline,
+                target,
+                pipelines,
+                pipeline_key,
                 uniform_buffer,
-                uniform_bind_group,
             },
         })
     }
@@ -190,117 +122,106 @@ impl State {
     pub fn create_object(&mut self, vertices: &[Vertex], indices: &[u16]) -> usize {
         let object = Object::new(&self.gpu.device, vertices, indices);
         self.objects.push(object);
-        self.objects.len() - 1
+
+        let object_id = self.objects.len() - 1;
+        self.bind_object(object_id);
+
+        object_id
     }
 
-    pub fn create_instance(&mut self, object_id: usize, position: cgmath::Vector3<f32>, rotation: cgmath::Quaternion<f32>) -> Option<usize> {
-        match self.objects.get_mut(object_id) {
-            Some(object) => {
-                object.add_instance(&self.gpu.device, position, rotation);
-
-                let uniform_bind_group_layout =
-                self.gpu.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                    bindings: &[
-                        wgpu::BindGroupLayoutEntry {
-                            binding: 0,
-                            visibility: wgpu::ShaderStage::VERTEX,
-                            ty: wgpu::BindingType::UniformBuffer { dynamic: false },
-                        },
-                        wgpu::BindGroupLayoutEntry {
-                            binding: 1,
-                            visibility: wgpu::ShaderStage::VERTEX,
-                            ty: wgpu::BindingType::StorageBuffer {
-                                // We don't plan on changing the size of this buffer
-                                dynamic: false,
-                                // The shader is not allowed to modify it's contents
-                                readonly: true,
-                            },
-                        },
-                    ],
-                    label: Some("uniform_bind_group_layout"),
-                });
-        
-                let uniform_bind_group = self.gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
-                    layout: &uniform_bind_group_layout,
-                    bindings: &[
-                        wgpu::Binding {
-                            binding: 0,
-                            resource: wgpu::BindingResource::Buffer {
-                                buffer: &self.gpu.uniform_buffer,
-                                // FYI: you can share a single buffer between bindings.
-                                range: 0..std::mem::size_of_val(&self.uniforms) as wgpu::BufferAddress,
-                            },
-                        },
-                        wgpu::Binding {
-                            binding: 1,
-                            resource: wgpu::BindingResource::Buffer {
-                                buffer: &object.instance_buffer(),
-                                range: 0..object.instance_buffer_size() as wgpu::BufferAddress,
-                            },
-                        },
-                    ],
-                    label: Some("uniform_bind_group"),
-                });
-        
-                self.gpu.uniform_bind_group = uniform_bind_group;
-
-                let mut compiler = shaders::ShaderCompiler::new().unwrap();
-                let vs_module = shaders::basic::vertex_module(&self.gpu.device, &mut compiler).unwrap();
-                let fs_module = shaders::basic::fragment_module(&self.gpu.device, &mut compiler).unwrap();
-
-                let render_pipeline_layout =
-                self.gpu.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                    bind_group_layouts: &[&uniform_bind_group_layout],
-                });
-    
-                self.gpu.render_pipeline = self.gpu.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                    layout: &render_pipeline_layout,
-                    vertex_stage: wgpu::ProgrammableStageDescriptor {
-                        module: &vs_module,
-                        entry_point: "main",
-                    },
-                    fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
-                        module: &fs_module,
-                        entry_point: "main",
-                    }),
-                    rasterization_state: Some(wgpu::RasterizationStateDescriptor {
-                        front_face: wgpu::FrontFace::Ccw,
-                        cull_mode: wgpu::CullMode::Back,
-                        depth_bias: 0,
-                        depth_bias_slope_scale: 0.0,
-                        depth_bias_clamp: 0.0,
-                    }),
-                    primitive_topology: wgpu::PrimitiveTopology::TriangleList,
-                    color_states: &[wgpu::ColorStateDescriptor {
-                        format: self.gpu.sc_desc.format,
-                        color_blend: wgpu::BlendDescriptor::REPLACE,
-                        alpha_blend: wgpu::BlendDescriptor::REPLACE,
-                        write_mask: wgpu::ColorWrite::ALL,
-                    }],
-                    depth_stencil_state: None,
-                    vertex_state: wgpu::VertexStateDescriptor {
-                        index_format: wgpu::IndexFormat::Uint16,
-                        vertex_buffers: &[Vertex::descriptor()],
-                    },
-                    sample_count: 1,
-                    sample_mask: !0,
-                    alpha_to_coverage_enabled: false,
-                });
-        
-                Some(object.num_instances() - 1)
-            },
-            None => None
+    pub fn create_instance(&mut self, object_id: usize, position: cgmath::Vector3<f32>, rotation: cgmath::Quaternion<f32>) -> Option<InstanceId> {
+        self.add_instance(object_id, Instance::new(position, rotation))
+    }
+
+    pub fn add_instance(&mut self, object_id: usize, instance: Instance) -> Option<InstanceId> {
+        let object = self.objects.get_mut(object_id)?;
+        Some(object.add_instance(instance))
+    }
+
+    /// Returns `None` if either the object or the instance doesn't exist.
+    pub fn update_instance(&mut self, object_id: usize, instance_id: InstanceId, instance: Instance) -> Option<()> {
+        let object = self.objects.get_mut(object_id)?;
+        if object.update_instance(instance_id, instance) {
+            Some(())
+        } else {
+            None
         }
     }
 
+    pub fn remove_instance(&mut self, object_id: usize, instance_id: InstanceId) -> Option<Instance> {
+        let object = self.objects.get_mut(object_id)?;
+        object.remove_instance(instance_id)
+    }
+
+    /// Replace every instance of an object, invalidating its existing instance handles.
+    pub fn set_instances(&mut self, object_id: usize, instances: Vec<Instance>) -> Option<Vec<InstanceId>> {
+        let object = self.objects.get_mut(object_id)?;
+        Some(object.set_instances(instances))
+    }
+
+    pub fn object(&self, object_id: usize) -> Option<&Object> {
+        self.objects.get(object_id)
+    }
+
+    pub fn camera(&self) -> &Camera {
+        &self.camera
+    }
+
+    pub fn camera_mut(&mut self) -> &mut Camera {
+        &mut self.camera
+    }
+
+    /// The point on the z = 0 plane under a window position in physical pixels.
+    pub fn screen_to_world(&self, x: f32, y: f32) -> Option<cgmath::Point2<f32>> {
+        self.camera
+            .screen_to_world(cgmath::Point2::new(x, y), self.size.width, self.size.height)
+    }
+
+    /// Where `point` is drawn in the window, in physical pixels.
+    pub fn world_to_screen(&self, point: cgmath::Point3<f32>) -> Option<cgmath::Point2<f32>> {
+        self.camera.world_to_screen(point, self.size.width, self.size.height)
+    }
+
+    /// The instance of an object under a window position in physical pixels.
+    pub fn pick_instance(&self, object_id: usize, x: f32, y: f32) -> Option<InstanceId> {
+        let ray = self
+            .camera
+            .screen_to_world_ray(cgmath::Point2::new(x, y), self.size.width, self.size.height)?;
+        self.objects.get(object_id)?.pick(&ray)
+    }
+
+    /// Apply changed graphics and camera settings without recreating the `State`.
+    pub fn apply_config(&mut self, config: &GraphicsConfig, camera: &CameraConfig) {
+        self.gpu.target.set_present_mode(&self.gpu.device, config.present_mode);
+
+        if config.hot_reload_shaders != self.gpu.pipelines.hot_reload_enabled() {
+            if !config.hot_reload_shaders {
+                self.gpu.pipelines.disable_hot_reload();
+            } else if let Err(error) = self.gpu.pipelines.enable_hot_reload() {
+                log::warn!("Shader hot reloading is unavailable: {:#}", error);
+            }
+        }
+
+        self.camera.apply_config(camera);
+        self.camera_controller.set_speed(camera.speed as f32);
+        self.config = config.clone();
+    }
+
+    /// (Re)create an object's bind group, pointing at its current instance buffer.
+    fn bind_object(&mut self, object_id: usize) {
+        self.objects[object_id].bind(
+            &self.gpu.device,
+            self.gpu.pipelines.bind_group_layout(),
+            &self.gpu.uniform_buffer,
+        );
+    }
+
     pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
         self.size = new_size;
-        self.gpu.sc_desc.width = new_size.width;
-        self.gpu.sc_desc.height = new_size.height;
-        self.gpu.swap_chain = self
-            .gpu
-            .device
-            .create_swap_chain(&self.gpu.surface, &self.gpu.sc_desc);
+        self.camera.aspect = new_size.width as f32 / new_size.height as f32;
+        self.gpu
+            .target
+            .resize(&self.gpu.device, new_size.width, new_size.height);
     }
 
     pub fn input(&mut self, event: &WindowEvent) -> bool {
@@ -308,6 +229,8 @@ impl State {
     }
 
     pub fn update(&mut self) {
+        self.gpu.pipelines.reload_changed_shaders(&self.gpu.device);
+
         self.camera_controller.update_camera(&mut self.camera);
         self.uniforms.update_view_proj(&self.camera);
 
@@ -339,12 +262,48 @@ impl State {
     }
 
     pub fn render(&mut self) {
-        let frame = self
-            .gpu
-            .swap_chain
-            .get_next_texture()
-            .expect("Timeout getting texture");
+        self.upload_instances();
+
+        match &mut self.gpu.target {
+            RenderTarget::Window { swap_chain, .. } => {
+                let frame = swap_chain
+                    .get_next_texture()
+                    .expect("Timeout getting texture");
+                self.render_to(&frame.view);
+            }
+            RenderTarget::Offscreen { .. } => self.render_offscreen(),
+        }
+    }
+
+    /// Render a frame and read it back from the GPU.
+    ///
+    /// Only works for a `State` created with [`State::new_offscreen`].
+    pub async fn capture_frame(&mut self) -> anyhow::Result<image::RgbaImage> {
+        self.render();
+        self.gpu.target.read_output(&self.gpu.device).await
+    }
+
+    /// Push instance changes made since the last frame to the GPU.
+    fn upload_instances(&mut self) {
+        for object_id in 0..self.objects.len() {
+            if self.objects[object_id].upload(&self.gpu.device, &self.gpu.queue) {
+                self.bind_object(object_id);
+            }
+        }
+    }
 
+    fn render_offscreen(&self) {
+        if let RenderTarget::Offscreen { view, .. } = &self.gpu.target {
+            self.render_to(view);
+        }
+    }
+
+    fn clear_color(&self) -> wgpu::Color {
+        let [r, g, b, a] = self.config.clear_color;
+        wgpu::Color { r, g, b, a }
+    }
+
+    fn render_to(&self, view: &wgpu::TextureView) {
         let mut encoder = self
             .gpu
             .device
@@ -355,21 +314,30 @@ impl State {
         {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
-                    attachment: &frame.view,
+                    attachment: view,
                     resolve_target: None,
                     load_op: wgpu::LoadOp::Clear,
                     store_op: wgpu::StoreOp::Store,
-                    clear_color: self.config.clear_color,
+                    clear_color: self.clear_color(),
                 }],
                 depth_stencil_attachment: None,
             });
 
-            render_pass.set_pipeline(&self.gpu.render_pipeline);
-            render_pass.set_bind_group(0, &self.gpu.uniform_bind_group, &[]);
+            let pipeline = self
+                .gpu
+                .pipelines
+                .get(&self.gpu.pipeline_key)
+                .expect("Render pipeline is built when the State is created");
+            render_pass.set_pipeline(pipeline);
 
             for object in &self.objects {
                 let num_instanaces = object.num_instances() as u32;
-                if num_instanaces > 0 {
+                if num_instanaces == 0 {
+                    continue;
+                }
+
+                if let Some(bind_group) = object.bind_group() {
+                    render_pass.set_bind_group(0, bind_group, &[]);
                     render_pass.set_vertex_buffer(0, object.vertex_buffer(), 0, 0);
                     render_pass.set_index_buffer(object.index_buffer(), 0, 0);
                     render_pass.draw_indexed(0..object.num_indices(), 0, 0..num_instanaces);                
@@ -377,6 +345,8 @@ impl State {
             }
         }
 
+        self.gpu.target.copy_to_output(&mut encoder);
+
         self.gpu.queue.submit(&[encoder.finish()]);
     }
 }
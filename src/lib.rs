@@ -0,0 +1,4 @@
+pub mod app;
+pub mod common;
+pub mod ecs;
+pub mod graphics;
@@ -0,0 +1,31 @@
+use blobbin::app::collision::{self, Contact};
+use blobbin::app::{Blob, BlobId};
+use blobbin::common::math::Vector2f;
+
+/// Separating blobs moves where they were last tick by as much as where they
+/// are, so drawing them between the two doesn't jump.
+#[test]
+fn separation_moves_previous_positions_too() {
+    let mut a = Blob::new(BlobId::new(0), 10.0, (0.0, 0.0));
+    let mut b = Blob::new(BlobId::new(1), 10.0, (6.0, 0.0));
+    a.mass = 1.0;
+    b.mass = 3.0;
+    a.previous_position = Vector2f::new(-2.0, 0.0);
+    b.previous_position = Vector2f::new(8.0, 0.0);
+
+    let contact = collision::circle_contact(&a, &b).unwrap();
+    assert_eq!(
+        contact,
+        Contact {
+            normal: Vector2f::new(1.0, 0.0),
+            depth: 4.0
+        }
+    );
+    collision::resolve(&mut a, &mut b, &contact, 0.0, 0.0);
+
+    // The lighter blob takes three quarters of the correction
+    assert_eq!(a.position, Vector2f::new(-3.0, 0.0));
+    assert_eq!(a.previous_position, Vector2f::new(-5.0, 0.0));
+    assert_eq!(b.position, Vector2f::new(7.0, 0.0));
+    assert_eq!(b.previous_position, Vector2f::new(9.0, 0.0));
+}
@@ -0,0 +1,129 @@
+use blobbin::common::config::{
+    BoundaryMode, ConfigLoader, ForceConfig, IntegratorKind, PhysicsConfig, PresentMode,
+};
+use blobbin::common::watch;
+
+fn env(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+    vars.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+}
+
+#[test]
+fn env_overrides_config_keys() {
+    let resolved = ConfigLoader::new()
+        .env(env(&[("BLOBBIN_PHYSICS__REPEL_FORCE", "20"), ("BLOBBIN_CAMERA__FOVY", "60")]))
+        .load()
+        .unwrap();
+
+    assert_eq!(resolved.config.physics.repel_force, 20.0);
+    assert_eq!(resolved.config.camera.fovy, 60.0);
+}
+
+#[test]
+fn unrelated_env_vars_are_skipped() {
+    let resolved = ConfigLoader::new()
+        .env(env(&[("BLOBBIN_LOG", "debug"), ("BLOBBIN_PHYSICS__NOT_A_KEY", "1")]))
+        .load()
+        .unwrap();
+
+    assert_eq!(resolved.config, Default::default());
+}
+
+#[test]
+fn invalid_env_values_still_fail() {
+    let result = ConfigLoader::new()
+        .env(env(&[("BLOBBIN_PHYSICS__REPEL_FORCE", "lots")]))
+        .load();
+
+    assert!(result.is_err());
+}
+
+/// Keys from before the config had sections still work, from every layer.
+#[test]
+fn flat_keys_move_into_physics() {
+    let path = std::env::temp_dir().join(format!("blobbin-flat-{}.json", std::process::id()));
+    std::fs::write(&path, r#"{ "blob_size": 8, "repel_distance": 12 }"#).unwrap();
+
+    let resolved = ConfigLoader::new()
+        .file(&path)
+        .env(env(&[("BLOBBIN_REPEL_FORCE", "20")]))
+        .set("friction_force", "2")
+        .load();
+    std::fs::remove_file(&path).unwrap();
+    let config = resolved.unwrap().config;
+
+    assert_eq!(config.physics.blob_size, 8.0);
+    assert_eq!(config.physics.repel_distance, 12.0);
+    assert_eq!(config.physics.repel_force, 20.0);
+    assert_eq!(config.physics.friction_force, 2.0);
+}
+
+#[test]
+fn named_values_round_trip() {
+    let resolved = ConfigLoader::new()
+        .env(env(&[
+            ("BLOBBIN_PHYSICS__INTEGRATOR", "rk4"),
+            ("BLOBBIN_PHYSICS__BOUNDARY__MODE", "wrap"),
+            ("BLOBBIN_GRAPHICS__PRESENT_MODE", "mailbox"),
+        ]))
+        .load()
+        .unwrap();
+
+    assert_eq!(resolved.config.physics.integrator, IntegratorKind::Rk4);
+    assert_eq!(resolved.config.physics.boundary.mode, BoundaryMode::Wrap);
+    assert_eq!(resolved.config.graphics.present_mode, PresentMode::Mailbox);
+
+    let error = ConfigLoader::new()
+        .env(env(&[("BLOBBIN_PHYSICS__COLLISIONS__SHAPE", "triangle")]))
+        .load()
+        .unwrap_err();
+    assert!(
+        format!("{:#}", error).contains("unknown collision shape \"triangle\", expected circle or aabb"),
+        "{:#}",
+        error
+    );
+}
+
+#[test]
+fn forces_round_trip_through_binary_formats() {
+    let physics = PhysicsConfig {
+        forces: vec![
+            ForceConfig::Repulsion { strength: 1.0, range: 2.0 },
+            ForceConfig::Attraction { strength: 3.0, range: 4.0 },
+            ForceConfig::LennardJones { epsilon: 5.0, sigma: 6.0, range: 7.0 },
+            ForceConfig::Gravity { x: 8.0, y: 9.0, strength: 10.0 },
+            ForceConfig::Alignment { strength: 11.0, range: 12.0 },
+            ForceConfig::Cohesion { strength: 13.0, range: 14.0 },
+            ForceConfig::Separation { strength: 15.0, range: 16.0 },
+            ForceConfig::Wind { x: 17.0, y: 18.0 },
+            ForceConfig::Drag { coefficient: 19.0 },
+        ],
+        ..PhysicsConfig::default()
+    };
+
+    let bytes = bincode::serialize(&physics).unwrap();
+    assert_eq!(bincode::deserialize::<PhysicsConfig>(&bytes).unwrap(), physics);
+
+    let json = serde_json::to_string(&physics).unwrap();
+    assert!(json.contains(r#"{"type":"lennard_jones","epsilon":5.0,"sigma":6.0,"range":7.0}"#));
+    assert_eq!(serde_json::from_str::<PhysicsConfig>(&json).unwrap(), physics);
+}
+
+/// Config files are told apart by their whole path, not just their name.
+#[test]
+fn watched_files_resolve_to_full_paths() {
+    let dir = std::env::temp_dir().join(format!("blobbin-watch-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("nested")).unwrap();
+    let root = dir.canonicalize().unwrap();
+
+    // The file doesn't need to exist yet
+    assert_eq!(
+        watch::resolve_file(&dir.join("nested/../config.json")),
+        root.join("config.json")
+    );
+    assert_ne!(
+        watch::resolve_file(&dir.join("nested/config.json")),
+        watch::resolve_file(&dir.join("config.json"))
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
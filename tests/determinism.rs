@@ -0,0 +1,91 @@
+use blobbin::app::{InputLog, Scene, SceneFormat, World, WorldInput};
+use blobbin::common::config::PhysicsConfig;
+
+const TICKS: u64 = 300;
+const DELTA_TIME: f64 = 1.0 / 60.0;
+
+/// Run a fresh world through `log` for `TICKS` ticks and hash the result.
+fn run(seed: u64, log: &InputLog) -> u64 {
+    let mut world = World::with_seed(300, 200, PhysicsConfig::default(), seed);
+    for _ in 0..TICKS {
+        world.replay_tick(log, DELTA_TIME);
+    }
+    world.state_hash()
+}
+
+fn inputs() -> InputLog {
+    let mut log = InputLog::new();
+    log.push(0, WorldInput::AddRandomBlobs { count: 200 });
+    log.push(40, WorldInput::AddBlob { x: 150.0, y: 100.0, species: 0 });
+    log.push(90, WorldInput::AddRandomBlobs { count: 50 });
+    log
+}
+
+#[test]
+fn same_seed_and_inputs_give_the_same_world() {
+    let log = inputs();
+
+    assert_eq!(run(7, &log), run(7, &log));
+    assert_ne!(run(7, &log), run(8, &log));
+}
+
+/// Config reloads are logged as inputs, so a replay that spans one ends up
+/// where the original run did.
+#[test]
+fn replays_span_config_changes() {
+    let mut config = PhysicsConfig::default();
+    config.repel_force *= 4.0;
+    config.friction_force = 0.0;
+
+    let mut log = inputs();
+    log.push(150, WorldInput::SetConfig { config });
+
+    // Apply inputs live, the way `App` does, logging them as they happen
+    let mut world = World::with_seed(300, 200, PhysicsConfig::default(), 7);
+    let mut live_log = InputLog::new();
+    for _ in 0..TICKS {
+        for input in log.inputs_at(world.tick()) {
+            world.apply_input(input);
+            live_log.push(world.tick(), input.clone());
+        }
+        world.update(DELTA_TIME);
+    }
+
+    assert_eq!(world.state_hash(), run(7, &live_log));
+    assert_ne!(world.state_hash(), run(7, &inputs()));
+}
+
+/// Saving and loading a scene mid-run doesn't change what happens next,
+/// including where random blobs are added.
+#[test]
+fn loaded_scenes_carry_on_where_they_were_saved() {
+    let log = inputs();
+    let mut world = World::with_seed(300, 200, PhysicsConfig::default(), 7);
+    for _ in 0..TICKS / 2 {
+        world.replay_tick(&log, DELTA_TIME);
+    }
+
+    let bytes = world.to_scene().to_bytes(SceneFormat::Json).unwrap();
+    let mut loaded = World::from_scene(Scene::from_bytes(SceneFormat::Json, &bytes).unwrap());
+    assert_eq!(loaded.state_hash(), world.state_hash());
+
+    for _ in TICKS / 2..TICKS {
+        world.replay_tick(&log, DELTA_TIME);
+        loaded.replay_tick(&log, DELTA_TIME);
+    }
+    world.apply_input(&WorldInput::AddRandomBlobs { count: 20 });
+    loaded.apply_input(&WorldInput::AddRandomBlobs { count: 20 });
+
+    assert_eq!(loaded.state_hash(), world.state_hash());
+}
+
+#[test]
+fn fresh_worlds_save_and_load() {
+    let world = World::with_seed(300, 200, PhysicsConfig::default(), 7);
+    let mut loaded = World::from_scene(world.to_scene());
+    loaded.add_random_blobs(10);
+
+    let mut expected = World::with_seed(300, 200, PhysicsConfig::default(), 7);
+    expected.add_random_blobs(10);
+    assert_eq!(loaded.state_hash(), expected.state_hash());
+}
@@ -0,0 +1,116 @@
+use blobbin::common::math::Vector2f;
+use blobbin::ecs::{
+    Component, EntityAllocator, Read, Schedule, SparseSetStorage, Storage, VecStorage, World, Write,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+struct Position(Vector2f);
+
+impl Component for Position {
+    type Storage = VecStorage<Self>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Velocity(Vector2f);
+
+impl Component for Velocity {
+    type Storage = VecStorage<Self>;
+}
+
+/// Only a few entities are selected at once.
+#[derive(Debug, Clone, PartialEq)]
+struct Selected;
+
+impl Component for Selected {
+    type Storage = SparseSetStorage<Self>;
+}
+
+#[test]
+fn reused_indices_get_a_new_generation() {
+    let mut entities = EntityAllocator::new();
+    let first = entities.allocate();
+    let second = entities.allocate();
+
+    assert!(entities.deallocate(first));
+    assert!(!entities.deallocate(first));
+
+    let reused = entities.allocate();
+    assert_eq!(reused.index(), first.index());
+    assert_ne!(reused.generation(), first.generation());
+    assert!(!entities.is_alive(first));
+    assert!(entities.is_alive(reused));
+    assert_eq!(entities.iter().collect::<Vec<_>>(), vec![reused, second]);
+}
+
+#[test]
+fn sparse_sets_stay_packed_after_removal() {
+    let mut storage = SparseSetStorage::default();
+    for index in [3, 7, 1].iter() {
+        storage.insert(*index, *index * 10);
+    }
+
+    assert_eq!(storage.remove(3), Some(30));
+    assert_eq!(storage.len(), 2);
+    assert_eq!(storage.get(1), Some(&10));
+    assert_eq!(storage.get(7), Some(&70));
+    assert_eq!(storage.get(3), None);
+    assert_eq!(storage.components().len(), storage.indices().len());
+}
+
+#[test]
+fn despawning_removes_components() {
+    let mut world = World::new();
+    let entity = world.spawn();
+    world
+        .insert(entity, Position(Vector2f::new(1.0, 2.0)))
+        .unwrap();
+    world.insert(entity, Selected).unwrap();
+    assert!(world.has::<Selected>(entity));
+
+    assert!(world.despawn(entity));
+    assert!(world.get::<Position>(entity).is_none());
+
+    // The index is reused, but the new entity starts without components
+    let reused = world.spawn();
+    assert_eq!(reused.index(), entity.index());
+    assert!(world.get::<Position>(reused).is_none());
+    assert!(!world.has::<Selected>(reused));
+    assert_eq!(world.insert(entity, Selected), Err(Selected));
+}
+
+#[test]
+fn queries_only_match_entities_with_every_component() {
+    let mut world = World::new();
+    let moving = world.spawn();
+    world
+        .insert(moving, Position(Vector2f::new(0.0, 0.0)))
+        .unwrap();
+    world
+        .insert(moving, Velocity(Vector2f::new(1.0, -1.0)))
+        .unwrap();
+    let still = world.spawn();
+    world
+        .insert(still, Position(Vector2f::new(5.0, 5.0)))
+        .unwrap();
+
+    let mut schedule =
+        Schedule::new().with_system(|world: &mut blobbin::ecs::World, delta_time: f64| {
+            world.query::<(Read<Velocity>, Write<Position>)>(|_, (velocity, position)| {
+                position.0 += delta_time * &velocity.0;
+            });
+        });
+    schedule.run(&mut world, 2.0);
+
+    assert_eq!(
+        *world.get::<Position>(moving).unwrap(),
+        Position(Vector2f::new(2.0, -2.0))
+    );
+    assert_eq!(
+        *world.get::<Position>(still).unwrap(),
+        Position(Vector2f::new(5.0, 5.0))
+    );
+
+    let mut matched = Vec::new();
+    world.query::<Read<Velocity>>(|entity, _| matched.push(entity));
+    assert_eq!(matched, vec![moving]);
+}
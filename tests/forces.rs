@@ -0,0 +1,81 @@
+use blobbin::app::World;
+use blobbin::common::config::{BoundaryMode, ForceConfig, PhysicsConfig, SpeciesConfig};
+
+fn config(mode: BoundaryMode) -> PhysicsConfig {
+    let mut config = PhysicsConfig::default();
+    config.boundary.mode = mode;
+    config.forces = vec![
+        ForceConfig::Cohesion { strength: 0.5, range: 25.0 },
+        ForceConfig::LennardJones { epsilon: 1.0, sigma: 4.0, range: 15.0 },
+    ];
+    config
+}
+
+fn assert_matches_brute_force(world: &mut World, seed: u64) {
+    let expected = world.brute_force_forces();
+    let mode = world.config.boundary.mode;
+    let actual = world.forces();
+    assert_eq!(actual.len(), expected.len());
+
+    for (index, (actual, expected)) in actual.iter().zip(&expected).enumerate() {
+        let error = (actual - expected).magnitude();
+        assert!(
+            error <= 1e-9 * expected.magnitude().max(1.0),
+            "seed {}, {:?}, blob {}: {:?} != {:?}",
+            seed,
+            mode,
+            index,
+            actual,
+            expected
+        );
+    }
+}
+
+/// The spatial hash only ever skips blobs that are out of range, so it finds
+/// the same forces as comparing every pair.
+#[test]
+fn spatial_hash_forces_match_brute_force() {
+    for seed in 0..8 {
+        for &mode in &[BoundaryMode::Reflect, BoundaryMode::Wrap] {
+            let mut world = World::with_seed(200, 150, config(mode), seed);
+            world.add_random_blobs(500);
+
+            assert_matches_brute_force(&mut world, seed);
+        }
+    }
+}
+
+/// In a wrapping world narrower than twice the force range, blobs are close
+/// across both edges at once but still only count once, at their nearest image.
+#[test]
+fn small_wrapping_worlds_count_each_neighbor_once() {
+    for seed in 0..8 {
+        let mut world = World::with_seed(40, 30, config(BoundaryMode::Wrap), seed);
+        world.add_random_blobs(60);
+
+        assert_matches_brute_force(&mut world, seed);
+    }
+}
+
+/// Positive interactions pull blobs towards the other species, negative ones
+/// push them away.
+#[test]
+fn species_interactions_pull_towards_positive_strengths() {
+    let config = PhysicsConfig {
+        repel_force: 0.0,
+        friction_force: 0.0,
+        species: vec![
+            SpeciesConfig { interactions: vec![0.0, 1.0], ..SpeciesConfig::default() },
+            SpeciesConfig { interactions: vec![-1.0, 0.0], ..SpeciesConfig::default() },
+        ],
+        ..PhysicsConfig::default()
+    };
+
+    let mut world = World::with_seed(200, 200, config, 0);
+    world.add_blob_of_species(100.0, 100.0, 0);
+    world.add_blob_of_species(110.0, 100.0, 1);
+
+    let forces = world.forces();
+    assert!(forces[0].x > 0.0, "{:?}", forces[0]);
+    assert!(forces[1].x > 0.0, "{:?}", forces[1]);
+}
@@ -0,0 +1,101 @@
+use blobbin::app::{World, WorldInput};
+use blobbin::common::config::{
+    BoundaryMode, ForceConfig, IntegratorKind, PhysicsConfig, SpeciesConfig,
+};
+use blobbin::common::math::Vector2f;
+
+const DELTA_TIME: f64 = 1.0 / 60.0;
+
+/// Only the forces in `forces`, with nothing else slowing blobs down or
+/// keeping them in.
+fn config(forces: Vec<ForceConfig>) -> PhysicsConfig {
+    let mut config = PhysicsConfig {
+        repel_force: 0.0,
+        friction_force: 0.0,
+        forces,
+        ..PhysicsConfig::default()
+    };
+    config.collisions.enabled = false;
+    config.boundary.mode = BoundaryMode::Unbounded;
+    config
+}
+
+/// How far the energy of a blob orbiting a point strays from where it
+/// started, as a fraction of it, over a few orbits.
+fn orbit_energy_drift(integrator: IntegratorKind) -> f64 {
+    let (radius, strength) = (50.0, 100.0);
+    let mut config = config(vec![ForceConfig::Gravity { x: 0.0, y: 0.0, strength }]);
+    config.integrator = integrator;
+
+    let mut world = World::with_seed(1000, 1000, config, 0);
+    let id = world.add_blob(radius, 0.0);
+    // Fast enough for the pull towards the center to keep it circling
+    let speed = (strength * radius).sqrt();
+    world.apply_input(&WorldInput::SetBlobVelocity { id, x: 0.0, y: speed });
+
+    let energy = |world: &World| {
+        let blob = world.blob(id).unwrap();
+        0.5 * blob.mass * blob.velocity.magnitude().powi(2) + strength * blob.position.magnitude()
+    };
+    let start = energy(&world);
+
+    let mut drift: f64 = 0.0;
+    for _ in 0..1000 {
+        world.update(DELTA_TIME);
+        drift = drift.max((energy(&world) - start).abs() / start);
+    }
+
+    drift
+}
+
+#[test]
+fn higher_order_integrators_drift_less() {
+    let euler = orbit_energy_drift(IntegratorKind::ExplicitEuler);
+    for &integrator in IntegratorKind::ALL.iter() {
+        let drift = orbit_energy_drift(integrator);
+        assert!(drift.is_finite(), "{:?}", integrator);
+        if integrator == IntegratorKind::VelocityVerlet || integrator == IntegratorKind::Rk4 {
+            assert!(drift < euler / 10.0, "{:?} drifted {} against {} for explicit Euler", integrator, drift, euler);
+        }
+    }
+}
+
+#[test]
+fn heavier_blobs_accelerate_less() {
+    let mut config = config(vec![ForceConfig::Wind { x: 6.0, y: 0.0 }]);
+    config.species = vec![
+        SpeciesConfig::default(),
+        SpeciesConfig {
+            mass: Some(3.0),
+            ..SpeciesConfig::default()
+        },
+    ];
+
+    let mut world = World::with_seed(1000, 1000, config, 0);
+    let light = world.add_blob_of_species(100.0, 100.0, 0);
+    let heavy = world.add_blob_of_species(200.0, 100.0, 1);
+    world.update(DELTA_TIME);
+
+    let acceleration = |id| world.blob(id).unwrap().acceleration.clone();
+    assert_eq!(acceleration(light), Vector2f::new(6.0, 0.0));
+    assert_eq!(acceleration(heavy), Vector2f::new(2.0, 0.0));
+}
+
+#[test]
+fn accelerations_below_the_minimum_are_dropped() {
+    let mut world = World::with_seed(1000, 1000, config(vec![ForceConfig::Wind { x: 0.0005, y: 0.0 }]), 0);
+    let id = world.add_blob(100.0, 100.0);
+    for _ in 0..10 {
+        world.update(DELTA_TIME);
+    }
+
+    let blob = world.blob(id).unwrap();
+    assert_eq!(blob.acceleration, Vector2f::ZERO);
+    assert_eq!(blob.velocity, Vector2f::ZERO);
+    assert_eq!(blob.position, Vector2f::new(100.0, 100.0));
+
+    let mut world = World::with_seed(1000, 1000, config(vec![ForceConfig::Wind { x: 0.0, y: 20000.0 }]), 0);
+    let id = world.add_blob(100.0, 100.0);
+    world.update(DELTA_TIME);
+    assert_eq!(world.blob(id).unwrap().acceleration, Vector2f::new(0.0, 10000.0));
+}
@@ -0,0 +1,130 @@
+use blobbin::app::recording::BlobState;
+use blobbin::app::{RecordedFrame, Recorder, Recording, World, WorldInput, RECORDING_VERSION};
+use blobbin::common::config::{ForceConfig, PhysicsConfig};
+use std::process::Command;
+
+/// `blobbin-sim` records the inputs that add its blobs, so rerunning them
+/// from the header ends up on the last recorded frame.
+#[test]
+fn sim_recordings_replay_as_inputs() {
+    let dir = std::env::temp_dir().join(format!("blobbin-recording-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("config.json"), "{}").unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_blobbin-sim"))
+        .current_dir(&dir)
+        .args(["--config", "config.json", "--output", "output.json", "--record", "sim.rec"])
+        .args(["--blobs", "50", "--ticks", "120", "--seed", "3"])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let recording = Recording::load(dir.join("sim.rec")).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let log = recording.input_log();
+    assert!(!log.is_empty());
+
+    let mut world = recording.header.world();
+    for _ in 1..recording.frames.len() {
+        world.replay_tick(&log, recording.header.step);
+    }
+
+    let last = recording.frames.last().unwrap();
+    assert_eq!(world.tick(), last.tick);
+    assert_eq!(world.blobs.len(), 50);
+    for (blob, recorded) in world.blobs.iter().zip(&last.blobs) {
+        assert_eq!(blob.id, recorded.id);
+        assert_eq!(blob.position, recorded.position);
+        assert_eq!(blob.velocity, recorded.velocity);
+    }
+}
+
+/// Recordings rerun from an empty world, so they can't start from a scene.
+#[test]
+fn sim_refuses_to_record_scenes() {
+    let output = Command::new(env!("CARGO_BIN_EXE_blobbin-sim"))
+        .args(["--scene", "start.scene", "--record", "sim.rec"])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--record can't be used with --scene"), "{}", stderr);
+}
+
+/// A corrupt blob count is rejected instead of allocating room for it.
+#[test]
+fn huge_blob_counts_are_rejected() {
+    let world = World::with_seed(100, 100, PhysicsConfig::default(), 0);
+    let mut bytes = Recorder::new(&world, 1.0 / 60.0).to_bytes();
+
+    // The only frame ends with its blob count, 0
+    assert_eq!(bytes.pop(), Some(0));
+    bytes.extend_from_slice(&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f]);
+
+    let error = Recording::from_bytes(&bytes).unwrap_err();
+    assert!(error.to_string().contains("truncated"), "{}", error);
+}
+
+fn frame(world: &World, inputs: Vec<WorldInput>) -> RecordedFrame {
+    RecordedFrame {
+        tick: world.tick(),
+        inputs,
+        blobs: world.blobs.iter().map(BlobState::from).collect(),
+    }
+}
+
+/// Every kind of input, and the frames around it, comes back as recorded.
+#[test]
+fn recordings_round_trip() {
+    let mut world = World::with_seed(200, 100, PhysicsConfig::default(), 5);
+    let mut recorder = Recorder::new(&world, 1.0 / 60.0);
+    let mut frames = vec![frame(&world, Vec::new())];
+
+    let config = PhysicsConfig {
+        forces: vec![ForceConfig::Wind { x: 3.0, y: 0.0 }],
+        ..PhysicsConfig::default()
+    };
+    for tick in 0..20 {
+        let inputs = match tick {
+            0 => vec![WorldInput::AddRandomBlobs { count: 10 }],
+            5 => vec![
+                WorldInput::AddBlob { x: 50.0, y: 50.0, species: 0 },
+                WorldInput::SetConfig { config: config.clone() },
+            ],
+            12 => vec![WorldInput::Resize { width: 300, height: 150 }],
+            _ => Vec::new(),
+        };
+        for input in &inputs {
+            world.apply_input(input);
+        }
+        world.update(1.0 / 60.0);
+        recorder.record(&world, &inputs);
+        frames.push(frame(&world, inputs));
+    }
+
+    let recording = Recording::from_bytes(&recorder.to_bytes()).unwrap();
+    assert_eq!(recording.header.seed, 5);
+    assert_eq!(recording.header.config, PhysicsConfig::default());
+    assert_eq!(recording.frames, frames);
+}
+
+/// Recordings only play back with the version that wrote them.
+#[test]
+fn other_versions_are_rejected() {
+    let world = World::with_seed(100, 100, PhysicsConfig::default(), 0);
+    let bytes = Recorder::new(&world, 1.0 / 60.0).to_bytes();
+
+    for version in [RECORDING_VERSION - 1, RECORDING_VERSION + 1].iter() {
+        let mut bytes = bytes.clone();
+        bytes[8..12].copy_from_slice(&version.to_le_bytes());
+
+        let error = Recording::from_bytes(&bytes).unwrap_err();
+        let expected = format!(
+            "Recording version {} can't be played back by version {}",
+            version, RECORDING_VERSION
+        );
+        assert_eq!(error.to_string(), expected);
+    }
+}
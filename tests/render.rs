@@ -0,0 +1,85 @@
+use futures::executor::block_on;
+
+use blobbin::common::config::{CameraConfig, GraphicsConfig};
+use blobbin::graphics::{self, color, Instance, State};
+
+const SIZE: u32 = 64;
+
+/// Renders a red square and a green circle side by side offscreen and checks
+/// both show up where they were put.
+///
+/// Needs a graphics adapter, so only runs with `cargo test -- --ignored`.
+#[test]
+#[ignore]
+fn renders_two_shapes() {
+    let config = GraphicsConfig {
+        hot_reload_shaders: false,
+        ..GraphicsConfig::default()
+    };
+    let mut state = block_on(State::new_offscreen(SIZE, SIZE, config, &CameraConfig::default()))
+        .expect("Rendering tests need a graphics adapter");
+    state.camera_mut().frame(cgmath::Point2::new(0.0, 0.0), 20.0);
+
+    let square = graphics::shape::square(color::RED);
+    let circle = graphics::shape::circle(color::GREEN, 32);
+    let square_object = state.create_object(&square.vertices, &square.indices);
+    let circle_object = state.create_object(&circle.vertices, &circle.indices);
+
+    let square_center = cgmath::Vector3::new(-5.0, 0.0, 0.0);
+    let circle_center = cgmath::Vector3::new(5.0, 0.0, 0.0);
+    for &(object, position) in &[(square_object, square_center), (circle_object, circle_center)] {
+        let mut instance = Instance::new(position, cgmath::Quaternion::new(1.0, 0.0, 0.0, 0.0));
+        instance.scale = 3.0;
+        state.add_instance(object, instance);
+    }
+
+    state.update();
+    let frame = block_on(state.capture_frame()).expect("Offscreen frames can be captured");
+
+    let pixel = |position: cgmath::Vector3<f32>| {
+        let point = cgmath::Point3::new(position.x, position.y, position.z);
+        let screen = state.world_to_screen(point).expect("Both shapes are in front of the camera");
+        frame.get_pixel(screen.x as u32, screen.y as u32).0
+    };
+
+    let [r, g, b, _] = pixel(square_center);
+    assert!(r > 200 && g < 50 && b < 50, "Square should be red, found {:?}", [r, g, b]);
+    let [r, g, b, _] = pixel(circle_center);
+    assert!(g > 200 && r < 50 && b < 50, "Circle should be green, found {:?}", [r, g, b]);
+
+    let [r, g, b, _] = frame.get_pixel(0, 0).0;
+    assert!(r < 50 && g < 50 && b < 50, "Background should be clear, found {:?}", [r, g, b]);
+}
+
+/// Captures an empty frame of an odd size, so rows need their padding
+/// stripped, and checks it comes back as the clear color in RGBA order.
+///
+/// Needs a graphics adapter, so only runs with `cargo test -- --ignored`.
+#[test]
+#[ignore]
+fn captures_frames_at_their_size_in_rgba() {
+    let (width, height) = (50, 30);
+    let config = GraphicsConfig {
+        hot_reload_shaders: false,
+        // Linear 0.214 is about half brightness once encoded as sRGB
+        clear_color: [1.0, 0.0, 0.214, 1.0],
+        ..GraphicsConfig::default()
+    };
+    let mut state = block_on(State::new_offscreen(width, height, config, &CameraConfig::default()))
+        .expect("Rendering tests need a graphics adapter");
+
+    let frame = block_on(state.capture_frame()).expect("Offscreen frames can be captured");
+    assert_eq!(frame.dimensions(), (width, height));
+    assert_eq!(frame.as_raw().len(), (width * height * 4) as usize);
+
+    for (x, y, pixel) in frame.enumerate_pixels() {
+        let [r, g, b, a] = pixel.0;
+        assert!(
+            r == 255 && g == 0 && (125..=130).contains(&b) && a == 255,
+            "Pixel ({}, {}) should be the clear color, found {:?}",
+            x,
+            y,
+            pixel.0
+        );
+    }
+}
@@ -0,0 +1,195 @@
+use blobbin::app::{BlobId, Scene, SceneFormat, World, SCENE_VERSION};
+use blobbin::common::config::{
+    BoundaryConfig, BoundaryMode, CollisionConfig, CollisionShape, ForceConfig, IntegratorKind,
+    PhysicsConfig,
+};
+use blobbin::common::math::Vector2f;
+use serde::Serialize;
+
+const SETTINGS: (f64, f64, f64, f64, f64, f64) = (4.0, 20.0, 12.0, 0.5, 5000.0, 0.01);
+
+fn forces() -> Vec<ForceConfig> {
+    vec![ForceConfig::Wind { x: 1.0, y: -2.0 }]
+}
+
+fn collisions() -> CollisionConfig {
+    CollisionConfig {
+        shape: CollisionShape::Aabb,
+        ..CollisionConfig::default()
+    }
+}
+
+fn boundary() -> BoundaryConfig {
+    BoundaryConfig {
+        mode: BoundaryMode::Wrap,
+        restitution: 0.5,
+    }
+}
+
+/// A blob as saved before species, sized twice `blob_size`.
+fn blob_v1(id: u64) -> (u64, f64, Vector2f, Vector2f, Vector2f, Vector2f) {
+    let position = Vector2f::new(10.0 * id as f64, 5.0);
+    (
+        id,
+        8.0,
+        position.clone(),
+        position,
+        Vector2f::new(1.0, 0.0),
+        Vector2f::new(0.0, 0.0),
+    )
+}
+
+/// A binary scene of `version` with 300x200, seed 7 and tick 30 in front of
+/// `rest`, which is the config and blobs laid out as that version saved them.
+fn binary<T: Serialize>(version: u32, rest: T) -> Vec<u8> {
+    let mut bytes = b"BLOBBIN\0".to_vec();
+    bytes.extend_from_slice(&version.to_le_bytes());
+    bytes.extend(bincode::serialize(&(version, 300usize, 200usize, 7u64, 30u64, rest)).unwrap());
+    bytes
+}
+
+/// Load `bytes` into a world and save it again, to see what was migrated.
+fn load(bytes: &[u8]) -> Scene {
+    World::from_scene(Scene::from_bytes(SceneFormat::Binary, bytes).unwrap()).to_scene()
+}
+
+fn assert_settings(config: &PhysicsConfig) {
+    assert_eq!(config.blob_size, 4.0);
+    assert_eq!(config.repel_force, 20.0);
+    assert_eq!(config.min_acceleration, 0.01);
+}
+
+fn assert_blobs(scene: &Scene, ids: &[u64]) {
+    assert_eq!(scene.blobs.len(), ids.len());
+    for (blob, id) in scene.blobs.iter().zip(ids) {
+        assert_eq!(blob.id, BlobId::new(*id));
+        assert_eq!(blob.position, Vector2f::new(10.0 * *id as f64, 5.0));
+        assert_eq!(blob.mass, 4.0);
+    }
+}
+
+#[test]
+fn scenes_round_trip_in_both_formats() {
+    let config = PhysicsConfig {
+        forces: forces(),
+        ..PhysicsConfig::default()
+    };
+    let mut world = World::with_seed(300, 200, config, 7);
+    world.add_random_blobs(20);
+    world.update(1.0 / 60.0);
+
+    for format in [SceneFormat::Json, SceneFormat::Binary].iter() {
+        let bytes = world.to_scene().to_bytes(*format).unwrap();
+        assert_eq!(
+            Scene::from_bytes(*format, &bytes).unwrap(),
+            world.to_scene()
+        );
+    }
+}
+
+#[test]
+fn version_1_binary_scenes_load() {
+    let scene = load(&binary(1, (SETTINGS, vec![blob_v1(0), blob_v1(1)])));
+    assert_settings(&scene.config);
+    assert!(scene.config.forces.is_empty());
+    assert_blobs(&scene, &[0, 1]);
+    assert_eq!(scene.tick, 30);
+
+    let scene = load(&binary(1, ((SETTINGS, forces()), vec![blob_v1(3)])));
+    assert_eq!(scene.config.forces, forces());
+    assert_blobs(&scene, &[3]);
+}
+
+#[test]
+fn version_2_binary_scenes_load() {
+    let integrator = IntegratorKind::Rk4;
+
+    let scene = load(&binary(
+        2,
+        ((SETTINGS, integrator, forces()), vec![blob_v1(0)]),
+    ));
+    assert_eq!(scene.config.integrator, integrator);
+    assert_eq!(scene.config.collisions, CollisionConfig::default());
+
+    let scene = load(&binary(
+        2,
+        (
+            (SETTINGS, integrator, forces(), collisions()),
+            vec![blob_v1(0)],
+        ),
+    ));
+    assert_eq!(scene.config.collisions, collisions());
+    assert_eq!(scene.config.boundary, BoundaryConfig::default());
+
+    let scene = load(&binary(
+        2,
+        (
+            (SETTINGS, integrator, forces(), collisions(), boundary()),
+            vec![blob_v1(0)],
+        ),
+    ));
+    assert_settings(&scene.config);
+    assert_eq!(scene.config.forces, forces());
+    assert_eq!(scene.config.boundary, boundary());
+    assert_blobs(&scene, &[0]);
+}
+
+#[test]
+fn version_3_and_4_binary_scenes_load() {
+    let config = (
+        SETTINGS,
+        IntegratorKind::VelocityVerlet,
+        forces(),
+        collisions(),
+        boundary(),
+    );
+    let scene = load(&binary(3, (config, vec![blob_v1(2)], 9u64)));
+    assert_eq!(scene.config.integrator, IntegratorKind::VelocityVerlet);
+    assert_blobs(&scene, &[2]);
+    assert_eq!(scene.next_blob_id, 9);
+
+    let config = PhysicsConfig {
+        blob_size: 4.0,
+        repel_force: 20.0,
+        min_acceleration: 0.01,
+        forces: forces(),
+        ..PhysicsConfig::default()
+    };
+    let (id, size, position, previous_position, velocity, acceleration) = blob_v1(1);
+    // Version 4 stored the blob's area as its mass
+    let blob = (
+        id,
+        0usize,
+        size,
+        64.0,
+        position,
+        previous_position,
+        velocity,
+        acceleration,
+    );
+    let scene = load(&binary(4, (config.clone(), vec![blob], 5u64)));
+    assert_eq!(scene.config, config);
+    assert_blobs(&scene, &[1]);
+    assert_eq!(scene.next_blob_id, 5);
+}
+
+#[test]
+fn mangled_and_newer_binary_scenes_are_rejected() {
+    let mut bytes = binary(3, (SETTINGS, vec![blob_v1(0)]));
+    let error = Scene::from_bytes(SceneFormat::Binary, &bytes).unwrap_err();
+    assert!(
+        error.to_string().contains("Not a version 3 binary scene"),
+        "{:#}",
+        error
+    );
+
+    bytes[8..12].copy_from_slice(&(SCENE_VERSION + 1).to_le_bytes());
+    let error = Scene::from_bytes(SceneFormat::Binary, &bytes).unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("newer than the newest supported version"),
+        "{:#}",
+        error
+    );
+}